@@ -0,0 +1,143 @@
+use chrono::NaiveDate;
+use icalendar::{Calendar as iCalendar, Property};
+
+use crate::event::{courses, normalize_name, CourseSummary, Event};
+use crate::{format, ics, text};
+
+/// A transformed calendar.
+pub struct Calendar {
+    /// Calendar properties and all components other than events.
+    inner: iCalendar,
+    events: Vec<Event>,
+}
+
+impl Calendar {
+    pub(crate) fn new(inner: iCalendar, events: Vec<Event>) -> Self {
+        Self { inner, events }
+    }
+
+    pub fn inner(&self) -> &iCalendar {
+        &self.inner
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// The distinct courses of the calendar, see [`courses`].
+    pub fn courses(&self) -> Vec<CourseSummary> {
+        courses(&self.events)
+    }
+
+    /// Splits the calendar into one calendar per course, named after the course.
+    ///
+    /// Events without a known course are left out.
+    pub fn split_by_course(&self) -> Vec<(CourseSummary, Calendar)> {
+        self.courses()
+            .into_iter()
+            .map(|course| {
+                let events = self
+                    .events
+                    .iter()
+                    .filter(|event| {
+                        event.course.as_ref().is_some_and(|other| {
+                            normalize_name(&other.full_name) == normalize_name(&course.full_name)
+                        })
+                    })
+                    .cloned()
+                    .collect();
+                let mut calendar = Calendar::new(self.inner.clone(), events);
+                calendar.set_name(&course.short_name);
+
+                (course, calendar)
+            })
+            .collect()
+    }
+
+    /// Sets the calendar name (`X-WR-CALNAME`).
+    pub fn set_name(&mut self, name: &str) {
+        self.inner
+            .properties
+            .retain(|property| property.key() != "X-WR-CALNAME");
+        self.inner
+            .append_property(Property::new("X-WR-CALNAME", text::escape(name)));
+    }
+
+    /// Appends a note to the calendar description (`X-WR-CALDESC`).
+    pub fn add_note(&mut self, note: &str) {
+        let mut cal_desc_prop = self
+            .inner
+            .properties
+            .iter_mut()
+            .filter(|property| property.key() == "X-WR-CALDESC");
+
+        if let Some(prop) = cal_desc_prop.next() {
+            let description = format!("{} {}", prop.value(), text::escape(note));
+            *prop = Property::new("X-WR-CALDESC", description);
+        } else {
+            self.inner
+                .append_property(Property::new("X-WR-CALDESC", text::escape(note)));
+        }
+    }
+
+    /// Serializes the calendar to iCalendar text.
+    pub fn to_ics(&self) -> String {
+        ics::to_ics(&self.inner, &self.events)
+    }
+
+    /// Serializes the events to JSON, see [`format::to_json`].
+    pub fn to_json(&self) -> String {
+        format::to_json(&self.events)
+    }
+
+    /// Serializes the events to CSV, see [`format::to_csv`].
+    pub fn to_csv(&self) -> String {
+        format::to_csv(&self.events)
+    }
+
+    /// Renders the events of a week as an HTML agenda, see [`format::to_agenda`].
+    pub fn to_agenda(&self, week: NaiveDate, link: impl Fn(NaiveDate) -> String) -> String {
+        format::to_agenda(&self.events, week, link)
+    }
+
+    /// Renders the events of a week as an SVG timetable, see [`format::to_timetable`].
+    pub fn to_timetable(&self, week: NaiveDate) -> String {
+        format::to_timetable(&self.events, week)
+    }
+
+    /// Renders the events of a week as plain text, see [`format::to_text`].
+    pub fn to_text(&self, week: NaiveDate) -> String {
+        format::to_text(&self.events, week)
+    }
+
+    /// Renders the events of a week as Markdown, see [`format::to_markdown`].
+    pub fn to_markdown(&self, week: NaiveDate) -> String {
+        format::to_markdown(&self.events, week)
+    }
+
+    /// Serializes the events to org-mode, see [`format::to_org`].
+    pub fn to_org(&self) -> String {
+        format::to_org(&self.events)
+    }
+
+    /// Bundles the calendar with one calendar per course as ZIP archive, see
+    /// [`format::to_zip`].
+    pub fn to_zip(&self) -> Vec<u8> {
+        format::to_zip(self)
+    }
+
+    /// Serializes the busy times within a date range, see [`format::to_freebusy`].
+    pub fn to_freebusy(&self, from: NaiveDate, to: NaiveDate) -> String {
+        format::to_freebusy(&self.events, from, to)
+    }
+
+    /// Serializes the calendar to jCal (RFC 7265).
+    pub fn to_jcal(&self) -> String {
+        format::to_jcal(&ics::tree(&self.inner, &self.events))
+    }
+
+    /// Serializes the calendar to xCal (RFC 6321).
+    pub fn to_xcal(&self) -> String {
+        format::to_xcal(&ics::tree(&self.inner, &self.events))
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use crate::utils;
+
+/// Building addresses and course name abbreviations of a university.
+pub enum DataSet {
+    /// The TUM data compiled into the library.
+    Builtin,
+    Custom {
+        buildings: HashMap<u16, String>,
+        /// Replacements sorted by descending length, so longer names are replaced first.
+        courses: Vec<(String, String)>,
+    },
+}
+
+impl DataSet {
+    /// Creates a data set from building codes to addresses and course names to abbreviations.
+    pub fn custom(buildings: HashMap<u16, String>, courses: HashMap<String, String>) -> Self {
+        let mut courses = courses.into_iter().collect::<Vec<_>>();
+        courses.sort_by_key(|(replacing, _)| std::cmp::Reverse(replacing.len()));
+
+        Self::Custom { buildings, courses }
+    }
+
+    pub fn match_building_id(&self, code: u16) -> Option<&str> {
+        match self {
+            Self::Builtin => utils::match_building_id(code),
+            Self::Custom { buildings, .. } => buildings.get(&code).map(|b| b.as_str()),
+        }
+    }
+
+    pub fn replace_course_name(&self, mut name: String) -> String {
+        match self {
+            Self::Builtin => utils::replace_course_name(name),
+            Self::Custom { courses, .. } => {
+                for (replacing, replaced) in courses {
+                    name = name.replace(replacing.as_str(), replaced.as_str());
+                }
+                name
+            }
+        }
+    }
+}
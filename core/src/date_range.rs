@@ -0,0 +1,254 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Days, Months, NaiveDate};
+use serde::{de, Deserialize, Deserializer};
+
+use crate::Semester;
+
+/// A day given either absolutely or relative to today.
+///
+/// Accepted are `YYYY-MM-DD`, `today`, `semester` (the start or end of the current semester,
+/// depending on the side of the range) and offsets of up to 100 years like `-30d`, `2w` or
+/// `-6m`. Offsets without a sign are positive, as a `+` in a query string decodes to a space.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DateExpr {
+    Date(NaiveDate),
+    Today,
+    Semester,
+    Days(i64),
+    Weeks(i64),
+    Months(i64),
+}
+
+/// Largest offset accepted in each unit, so that offsets resolve to representable dates.
+const MAX_OFFSET_YEARS: u64 = 100;
+
+#[derive(Debug)]
+pub struct InvalidDateExpr {
+    expr: String,
+}
+
+impl DateExpr {
+    /// Resolves the expression as the first day of a range.
+    pub fn resolve_start(&self, today: NaiveDate) -> Result<NaiveDate, InvalidDateExpr> {
+        match self {
+            Self::Semester => Ok(Semester::containing(today).start),
+            _ => self.resolve(today),
+        }
+    }
+
+    /// Resolves the expression as the last day of a range.
+    pub fn resolve_end(&self, today: NaiveDate) -> Result<NaiveDate, InvalidDateExpr> {
+        match self {
+            Self::Semester => Ok(Semester::containing(today).end),
+            _ => self.resolve(today),
+        }
+    }
+
+    fn resolve(&self, today: NaiveDate) -> Result<NaiveDate, InvalidDateExpr> {
+        let offset = |days: i64| {
+            if days < 0 {
+                today.checked_sub_days(Days::new(days.unsigned_abs()))
+            } else {
+                today.checked_add_days(Days::new(days.unsigned_abs()))
+            }
+        };
+
+        let resolved = match *self {
+            Self::Date(date) => Some(date),
+            Self::Today | Self::Semester => Some(today),
+            Self::Days(days) => offset(days),
+            Self::Weeks(weeks) => weeks.checked_mul(7).and_then(offset),
+            Self::Months(months) => {
+                let shifted = u32::try_from(months.unsigned_abs()).ok().map(Months::new);
+                if months < 0 {
+                    shifted.and_then(|months| today.checked_sub_months(months))
+                } else {
+                    shifted.and_then(|months| today.checked_add_months(months))
+                }
+            }
+        };
+
+        resolved.ok_or_else(|| InvalidDateExpr {
+            expr: self.to_string(),
+        })
+    }
+}
+
+impl FromStr for DateExpr {
+    type Err = InvalidDateExpr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidDateExpr {
+            expr: s.to_string(),
+        };
+
+        match s {
+            "today" => return Ok(Self::Today),
+            "semester" => return Ok(Self::Semester),
+            _ => {}
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(Self::Date(date));
+        }
+
+        // `+2w` arrives as ` 2w` if the `+` isn't percent-encoded
+        let offset = s.trim_start();
+        let Some(unit) = offset.chars().last() else {
+            return Err(invalid());
+        };
+        let number = &offset[..offset.len() - unit.len_utf8()];
+        let number = number.parse::<i64>().map_err(|_| invalid())?;
+        let (expr, max) = match unit {
+            'd' => (Self::Days(number), MAX_OFFSET_YEARS * 366),
+            'w' => (Self::Weeks(number), MAX_OFFSET_YEARS * 53),
+            'm' => (Self::Months(number), MAX_OFFSET_YEARS * 12),
+            _ => return Err(invalid()),
+        };
+        if number.unsigned_abs() > max {
+            return Err(invalid());
+        }
+
+        Ok(expr)
+    }
+}
+
+impl<'de> Deserialize<'de> for DateExpr {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<DateExpr>()
+            .map_err(|e| de::Error::custom(format!("{}", e)))
+    }
+}
+
+impl fmt::Display for DateExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            Self::Today => write!(f, "today"),
+            Self::Semester => write!(f, "semester"),
+            Self::Days(days) => write!(f, "{:+}d", days),
+            Self::Weeks(weeks) => write!(f, "{:+}w", weeks),
+            Self::Months(months) => write!(f, "{:+}m", months),
+        }
+    }
+}
+
+impl fmt::Display for InvalidDateExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid date: {}", self.expr)
+    }
+}
+
+impl std::error::Error for InvalidDateExpr {}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::DateExpr;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("Invalid date")
+    }
+
+    #[test]
+    fn parses_valid_expressions() {
+        let cases = [
+            ("2025-10-20", DateExpr::Date(date(2025, 10, 20))),
+            ("today", DateExpr::Today),
+            ("semester", DateExpr::Semester),
+            ("-30d", DateExpr::Days(-30)),
+            ("+2w", DateExpr::Weeks(2)),
+            ("2w", DateExpr::Weeks(2)),
+            (" 2w", DateExpr::Weeks(2)),
+            ("-6m", DateExpr::Months(-6)),
+            ("0d", DateExpr::Days(0)),
+            ("+1200m", DateExpr::Months(1200)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<DateExpr>().ok(), Some(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        let cases = [
+            "",
+            " ",
+            "d",
+            "+d",
+            "yesterday",
+            "2025-13-01",
+            "2w ",
+            "2y",
+            "++2w",
+            "2.5w",
+            "-3x",
+        ];
+        for input in cases {
+            assert!(input.parse::<DateExpr>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_non_ascii_expressions() {
+        for input in ["2ä", "-3€", "ü", "+２w", "2w\u{0301}"] {
+            assert!(input.parse::<DateExpr>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_offsets() {
+        let cases = [
+            "+36601d",
+            "-5301w",
+            "1201m",
+            "99999999999999999999d",
+            "-9223372036854775808d",
+        ];
+        for input in cases {
+            assert!(input.parse::<DateExpr>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn resolves_offsets() {
+        let today = date(2025, 10, 20);
+        let resolve = |input: &str| input.parse::<DateExpr>().unwrap().resolve_start(today);
+
+        assert_eq!(resolve("today").ok(), Some(today));
+        assert_eq!(resolve("-30d").ok(), Some(date(2025, 9, 20)));
+        assert_eq!(resolve("2w").ok(), Some(date(2025, 11, 3)));
+        assert_eq!(resolve("-6m").ok(), Some(date(2025, 4, 20)));
+        assert_eq!(resolve("2025-01-01").ok(), Some(date(2025, 1, 1)));
+        assert!(resolve("+36600d").is_ok());
+    }
+
+    #[test]
+    fn resolves_semester_per_side() {
+        let cases = [
+            (date(2025, 10, 20), date(2025, 10, 1), date(2026, 3, 31)),
+            (date(2026, 2, 1), date(2025, 10, 1), date(2026, 3, 31)),
+            (date(2026, 4, 1), date(2026, 4, 1), date(2026, 9, 30)),
+            (date(2026, 9, 30), date(2026, 4, 1), date(2026, 9, 30)),
+        ];
+        for (today, start, end) in cases {
+            assert_eq!(DateExpr::Semester.resolve_start(today).ok(), Some(start));
+            assert_eq!(DateExpr::Semester.resolve_end(today).ok(), Some(end));
+            assert_eq!(DateExpr::Today.resolve_end(today).ok(), Some(today));
+        }
+    }
+
+    #[test]
+    fn reports_unrepresentable_dates() {
+        let today = NaiveDate::MAX - chrono::Days::new(10);
+        assert!(DateExpr::Days(30).resolve_end(today).is_err());
+        assert!(DateExpr::Months(1).resolve_end(today).is_err());
+        assert!(DateExpr::Days(-30).resolve_end(today).is_ok());
+    }
+}
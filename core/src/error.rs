@@ -0,0 +1,18 @@
+use std::fmt;
+
+/// Errors that can occur while transforming a calendar.
+#[derive(Debug)]
+pub enum TransformError {
+    /// A component of the upstream calendar could not be converted.
+    InvalidComponent(String),
+}
+
+impl fmt::Display for TransformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidComponent(kind) => write!(f, "Could not convert {} component", kind),
+        }
+    }
+}
+
+impl std::error::Error for TransformError {}
@@ -0,0 +1,149 @@
+use chrono::DateTime;
+use chrono_tz::Tz;
+use icalendar::{Component, DatePerhapsTime, Event as iEvent};
+
+use crate::event_type::EventType;
+use crate::group::Group;
+use crate::utils::to_local_time;
+
+/// An event passing through the transformation pipeline.
+#[derive(Clone)]
+pub struct Event {
+    /// The event as it is written to the calendar.
+    pub inner: iEvent,
+    /// The summary as exported by CAMPUSonline.
+    pub summary: String,
+    /// Details parsed from the summary, if it has a known format.
+    pub course: Option<Course>,
+    /// The room as exported by CAMPUSonline, if it could be mapped to an address.
+    pub room: Option<String>,
+}
+
+/// Details about the course an event belongs to.
+#[derive(Clone, Debug)]
+pub struct Course {
+    /// The full course name.
+    pub full_name: String,
+    /// The name used as summary, abbreviated if possible.
+    pub short_name: String,
+    /// Course IDs, e.g. `IN0001`.
+    pub ids: Vec<String>,
+    pub typ: EventType,
+    /// The group as listed after the event type, e.g. `Standardgruppe`.
+    pub group: Group,
+}
+
+/// A course found in a calendar, with all of its events merged.
+#[derive(Clone, Debug)]
+pub struct CourseSummary {
+    /// Identifies the course in per-course feeds, see [`Course::key`].
+    pub key: String,
+    pub full_name: String,
+    pub short_name: String,
+    pub ids: Vec<String>,
+    pub types: Vec<EventType>,
+    /// Number of events of the course.
+    pub events: usize,
+}
+
+impl Course {
+    /// The normalized name, which identifies the course in [`courses`].
+    ///
+    /// Course IDs are not unique, e.g. the Zentralübung shares them with its lecture.
+    pub fn key(&self) -> String {
+        normalize_name(&self.full_name)
+    }
+
+    /// Whether the key is one of the course IDs or the (normalized) course name.
+    pub fn matches(&self, key: &str) -> bool {
+        self.ids.iter().any(|id| id.eq_ignore_ascii_case(key))
+            || normalize_name(&self.full_name) == normalize_name(key)
+    }
+}
+
+/// Lists the distinct courses of the events, in order of their first event.
+///
+/// Courses are identified by their normalized name, so events with and without course IDs
+/// end up in the same course.
+pub fn courses(events: &[Event]) -> Vec<CourseSummary> {
+    let mut courses: Vec<CourseSummary> = Vec::new();
+    for course in events.iter().filter_map(|event| event.course.as_ref()) {
+        let key = course.key();
+        let summary = match courses.iter_mut().position(|summary| summary.key == key) {
+            Some(index) => &mut courses[index],
+            None => {
+                courses.push(CourseSummary {
+                    key,
+                    full_name: course.full_name.clone(),
+                    short_name: course.short_name.clone(),
+                    ids: Vec::new(),
+                    types: Vec::new(),
+                    events: 0,
+                });
+                courses.last_mut().expect("Course was just added")
+            }
+        };
+
+        for id in &course.ids {
+            if !summary.ids.contains(id) {
+                summary.ids.push(id.clone());
+            }
+        }
+        if !summary.types.contains(&course.typ) {
+            summary.types.push(course.typ);
+        }
+        summary.events += 1;
+    }
+
+    courses
+}
+
+/// Lowercase name with umlauts replaced and everything else but letters and digits as `-`.
+pub fn normalize_name(name: &str) -> String {
+    let mut normalized = String::new();
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        match c {
+            'ä' => normalized.push_str("ae"),
+            'ö' => normalized.push_str("oe"),
+            'ü' => normalized.push_str("ue"),
+            'ß' => normalized.push_str("ss"),
+            c if c.is_alphanumeric() => normalized.push(c),
+            _ if !normalized.ends_with('-') => normalized.push('-'),
+            _ => {}
+        }
+    }
+
+    normalized.trim_matches('-').to_string()
+}
+
+impl Event {
+    pub(crate) fn new(inner: iEvent, summary: String) -> Self {
+        Self {
+            inner,
+            summary,
+            course: None,
+            room: None,
+        }
+    }
+
+    /// Start in local time.
+    pub fn start(&self) -> Option<DateTime<Tz>> {
+        self.inner.get_start().and_then(to_local_time)
+    }
+
+    /// End in local time.
+    pub fn end(&self) -> Option<DateTime<Tz>> {
+        self.inner.get_end().and_then(to_local_time)
+    }
+
+    /// Whether the event has further instances given by `RRULE` or `RDATE`.
+    pub fn is_recurring(&self) -> bool {
+        self.inner.property_value("RRULE").is_some()
+            || self.inner.multi_properties().contains_key("RDATE")
+    }
+
+    /// Whether the event lasts whole days instead of having a start time.
+    pub fn is_all_day(&self) -> bool {
+        matches!(self.inner.get_start(), Some(DatePerhapsTime::Date(_)))
+    }
+}
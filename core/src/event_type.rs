@@ -27,12 +27,21 @@ pub enum EventType {
     Fachpruefung,
 }
 
+#[derive(Clone)]
 pub enum Filter {
     Include(HashSet<EventType>),
     Exclude(HashSet<EventType>),
     None,
 }
 
+/// An entry of an include or exclude list, optionally scoped to a course ID or name, e.g. `VO`
+/// or `IN0001:UE`.
+#[derive(Clone, Debug)]
+pub struct ScopedEventType {
+    pub course: Option<String>,
+    pub typ: EventType,
+}
+
 #[derive(Debug)]
 pub struct InvalidEventType {
     id: String,
@@ -174,6 +183,36 @@ impl FromStr for EventType {
     }
 }
 
+impl FromStr for ScopedEventType {
+    type Err = InvalidEventType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Course names may contain colons, event types don't
+        match s.rsplit_once(':') {
+            Some((course, typ)) if !course.trim().is_empty() => Ok(Self {
+                course: Some(course.trim().to_string()),
+                typ: typ.trim().parse()?,
+            }),
+            Some(_) => Err(InvalidEventType { id: s.to_string() }),
+            None => Ok(Self {
+                course: None,
+                typ: s.parse()?,
+            }),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ScopedEventType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<ScopedEventType>()
+            .map_err(|e| de::Error::custom(format!("{}", e)))
+    }
+}
+
 impl fmt::Display for InvalidEventType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Invalid course type: {}", self.id)
@@ -0,0 +1,136 @@
+//! HTML agenda of a single week, grouped by day.
+
+use std::fmt::Write;
+
+use chrono::{Datelike, Days, NaiveDate};
+use icalendar::{Component, EventLike};
+
+use crate::event::Event;
+use crate::format::{escape, week_events, week_start, weekday_name};
+
+const STYLE: &str = r#"
+    * { box-sizing: border-box; }
+    body {
+        color: white;
+        font-family: "Nunito", sans-serif;
+        background-color: #171818;
+        margin: 0;
+        display: flex;
+        justify-content: center;
+    }
+    @media (prefers-color-scheme: light) {
+        body { color: black; background-color: #bbbcbc; }
+        .event { background-color: #d7d8d8; }
+    }
+    a { color: inherit; }
+    .content { width: 699px; padding: 8px; }
+    @media screen and (max-width: 699px) { .content { width: 100%; } }
+    h1 { font-weight: 800; text-align: center; margin: 0.5rem; }
+    nav { display: flex; justify-content: space-between; margin-bottom: 1rem; }
+    h2 { font-size: 1.1rem; margin: 1.5rem 0 0.5rem; }
+    .event { background-color: #2a2b2b; border-radius: 8px; padding: 0.5rem 0.75rem; margin: 0.5rem 0; }
+    .time { font-weight: 800; }
+    .title { font-weight: 800; }
+    .details { opacity: 0.8; font-size: 0.9rem; }
+    .empty { opacity: 0.6; }
+"#;
+
+/// Renders the events of the week containing `week` as an HTML page.
+///
+/// `link` returns the URL of the agenda for another week, used for the navigation.
+pub fn to_agenda(events: &[Event], week: NaiveDate, link: impl Fn(NaiveDate) -> String) -> String {
+    let monday = week_start(week);
+    let sunday = monday + Days::new(6);
+    let events = week_events(events, monday);
+
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"de\">\n<head>\n");
+    out.push_str("<meta charset=\"UTF-8\">\n");
+    out.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    out.push_str("<title>TUM Calendar Proxy – Agenda</title>\n");
+    out.push_str("<link rel=\"icon\" type=\"image/x-icon\" href=\"/favicon.ico\">\n");
+    writeln!(out, "<style>{}</style>\n</head>\n<body>", STYLE).expect("Could not write to string");
+    out.push_str("<div class=\"content\">\n");
+
+    writeln!(
+        out,
+        "<h1>KW {}: {} – {}</h1>",
+        monday.iso_week().week(),
+        monday.format("%d.%m."),
+        sunday.format("%d.%m.%Y")
+    )
+    .expect("Could not write to string");
+    writeln!(
+        out,
+        "<nav><a href=\"{}\">← Vorherige Woche</a><a href=\"{}\">Nächste Woche →</a></nav>",
+        escape(&link(monday - Days::new(7))),
+        escape(&link(monday + Days::new(7)))
+    )
+    .expect("Could not write to string");
+
+    for day in monday.iter_days().take(7) {
+        writeln!(
+            out,
+            "<h2>{}, {}</h2>",
+            weekday_name(day.weekday()),
+            day.format("%d.%m.%Y")
+        )
+        .expect("Could not write to string");
+
+        let mut any = false;
+        for event in events
+            .iter()
+            .filter(|event| event.start().map(|start| start.date_naive()) == Some(day))
+        {
+            any = true;
+            push_event(&mut out, event);
+        }
+        if !any {
+            out.push_str("<p class=\"empty\">Keine Termine</p>\n");
+        }
+    }
+
+    out.push_str("</div>\n</body>\n</html>\n");
+
+    out
+}
+
+fn push_event(out: &mut String, event: &Event) {
+    out.push_str("<div class=\"event\">\n");
+
+    let time = match (event.start(), event.end()) {
+        _ if event.is_all_day() => "Ganztägig".to_string(),
+        (Some(start), Some(end)) => format!("{} – {}", start.format("%H:%M"), end.format("%H:%M")),
+        (Some(start), None) => start.format("%H:%M").to_string(),
+        _ => String::new(),
+    };
+    let title = event.inner.get_summary().unwrap_or(&event.summary);
+    writeln!(
+        out,
+        "<div><span class=\"time\">{}</span> <span class=\"title\">{}</span></div>",
+        escape(&time),
+        escape(title)
+    )
+    .expect("Could not write to string");
+
+    let mut details = Vec::new();
+    if let Some(course) = &event.course {
+        if course.full_name != title {
+            details.push(course.full_name.clone());
+        }
+        details.push(format!("{} ({})", course.typ, course.typ.id()));
+        details.push(course.group.name.clone());
+    }
+    if let Some(room) = &event.room {
+        details.push(room.clone());
+    }
+    if let Some(location) = event.inner.get_location() {
+        details.push(location.to_string());
+    }
+    for detail in details {
+        writeln!(out, "<div class=\"details\">{}</div>", escape(&detail))
+            .expect("Could not write to string");
+    }
+
+    out.push_str("</div>\n");
+}
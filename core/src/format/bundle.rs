@@ -0,0 +1,63 @@
+//! ZIP archive with one iCalendar file per course and one with all events.
+
+use std::io::{Cursor, Write};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+use crate::Calendar;
+
+/// Name of the file containing all events.
+const COMBINED_NAME: &str = "Alle Kurse.ics";
+
+/// Bundles the calendar and its per-course calendars, named after the abbreviated course
+/// names.
+pub fn to_zip(calendar: &Calendar) -> Vec<u8> {
+    // A fixed modification time keeps the archive identical as long as the calendar is unchanged
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(DateTime::default());
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let mut names = vec![COMBINED_NAME.to_string()];
+    zip.start_file(COMBINED_NAME, options)
+        .expect("Could not write to ZIP archive");
+    zip.write_all(calendar.to_ics().as_bytes())
+        .expect("Could not write to ZIP archive");
+
+    for (course, course_calendar) in calendar.split_by_course() {
+        let name = file_name(&course.short_name, &names);
+        zip.start_file(name.as_str(), options)
+            .expect("Could not write to ZIP archive");
+        zip.write_all(course_calendar.to_ics().as_bytes())
+            .expect("Could not write to ZIP archive");
+        names.push(name);
+    }
+
+    zip.finish()
+        .expect("Could not write to ZIP archive")
+        .into_inner()
+}
+
+/// `<name>.ics` without characters that are invalid in file names, numbered if it is taken.
+fn file_name(name: &str, taken: &[String]) -> String {
+    let name = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>();
+    let name = name.trim_matches(['.', ' ']);
+    let name = if name.is_empty() { "Kurs" } else { name };
+
+    let mut file_name = format!("{}.ics", name);
+    let mut n = 2;
+    while taken.contains(&file_name) {
+        file_name = format!("{} ({}).ics", name, n);
+        n += 1;
+    }
+
+    file_name
+}
@@ -0,0 +1,87 @@
+//! CSV table of the transformed events, one row per event, sorted by start.
+//!
+//! Columns are date, weekday, start and end in local time, full course name, abbreviated
+//! name, event type ID, group, room and building address. Events with an unknown summary
+//! format only have their summary as name.
+
+use chrono::Datelike;
+use icalendar::{Component, EventLike};
+
+use crate::event::Event;
+use crate::format::weekday_name;
+
+const HEADER: [&str; 10] = [
+    "Datum",
+    "Wochentag",
+    "Beginn",
+    "Ende",
+    "Name",
+    "Abkürzung",
+    "Typ",
+    "Gruppe",
+    "Raum",
+    "Adresse",
+];
+
+/// Serializes the events to CSV.
+pub fn to_csv(events: &[Event]) -> String {
+    let mut events = events.iter().collect::<Vec<_>>();
+    events.sort_by_key(|event| event.start());
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(HEADER)
+        .expect("Could not write CSV record");
+
+    for event in events {
+        let start = event.start();
+        let title = event.inner.get_summary().unwrap_or(&event.summary);
+        let location = event.inner.get_location().unwrap_or_default();
+        let (room, address) = match &event.room {
+            Some(room) => (room.as_str(), location),
+            None => (location, ""),
+        };
+
+        let record = [
+            start
+                .map(|start| start.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            start
+                .map(|start| weekday_name(start.date_naive().weekday()).to_string())
+                .unwrap_or_default(),
+            start
+                .filter(|_| !event.is_all_day())
+                .map(|start| start.format("%H:%M").to_string())
+                .unwrap_or_default(),
+            event
+                .end()
+                .filter(|_| !event.is_all_day())
+                .map(|end| end.format("%H:%M").to_string())
+                .unwrap_or_default(),
+            event
+                .course
+                .as_ref()
+                .map(|course| course.full_name.clone())
+                .unwrap_or_else(|| title.to_string()),
+            title.to_string(),
+            event
+                .course
+                .as_ref()
+                .map(|course| course.typ.id().to_string())
+                .unwrap_or_default(),
+            event
+                .course
+                .as_ref()
+                .map(|course| course.group.name.clone())
+                .unwrap_or_default(),
+            room.to_string(),
+            address.to_string(),
+        ];
+        writer
+            .write_record(&record)
+            .expect("Could not write CSV record");
+    }
+
+    let bytes = writer.into_inner().expect("Could not write CSV");
+    String::from_utf8(bytes).expect("CSV is not valid UTF-8")
+}
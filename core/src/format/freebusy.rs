@@ -0,0 +1,191 @@
+//! Free/busy information (`VFREEBUSY`) without any details about the events.
+
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, TimeZone, Utc};
+use icalendar::{Component, Property};
+
+use crate::event::Event;
+use crate::ics::{self, Node};
+use crate::recurrence;
+use crate::TIMEZONE;
+
+/// Serializes the busy times between the start of `from` and the end of `to` as a calendar
+/// with a single `VFREEBUSY`, merging overlapping and adjacent events.
+///
+/// Cancelled and transparent events don't count as busy, recurring events count with each of
+/// their instances.
+pub fn to_freebusy(events: &[Event], from: NaiveDate, to: NaiveDate) -> String {
+    let start = local_midnight(from);
+    let end = to
+        .checked_add_days(Days::new(1))
+        .map_or(DateTime::<Utc>::MAX_UTC, local_midnight);
+
+    let mut periods = events
+        .iter()
+        .filter(|event| event.inner.property_value("STATUS") != Some("CANCELLED"))
+        .filter(|event| event.inner.property_value("TRANSP") != Some("TRANSPARENT"))
+        .flat_map(|event| {
+            let from = start.with_timezone(&TIMEZONE);
+            recurrence::occurrences(event, from, end.with_timezone(&TIMEZONE))
+        })
+        .filter_map(|event| {
+            let event_start = event.start()?.with_timezone(&Utc).max(start);
+            let event_end = event.end()?.with_timezone(&Utc).min(end);
+            (event_start < event_end).then_some((event_start, event_end))
+        })
+        .collect::<Vec<_>>();
+    periods.sort();
+
+    let mut merged: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for (period_start, period_end) in periods {
+        match merged.last_mut() {
+            Some((_, last_end)) if period_start <= *last_end => {
+                *last_end = (*last_end).max(period_end);
+            }
+            _ => merged.push((period_start, period_end)),
+        }
+    }
+
+    // Changes only with the events, so that the output stays the same between refreshes
+    let stamp = events
+        .iter()
+        .filter_map(|event| event.inner.property_value("DTSTAMP"))
+        .max()
+        .map_or_else(|| utc(start), str::to_string);
+
+    let mut properties = vec![
+        Property::new("DTSTAMP", stamp),
+        Property::new("DTSTART", utc(start)),
+        Property::new("DTEND", utc(end)),
+    ];
+    for (period_start, period_end) in merged {
+        let mut property = Property::new(
+            "FREEBUSY",
+            format!("{}/{}", utc(period_start), utc(period_end)),
+        );
+        property.add_parameter("FBTYPE", "BUSY");
+        properties.push(property);
+    }
+
+    ics::write(&Node {
+        kind: "VCALENDAR".to_string(),
+        properties: vec![
+            Property::new("VERSION", "2.0"),
+            Property::new("PRODID", "TUM-CalProxy/0.1"),
+            Property::new("METHOD", "PUBLISH"),
+        ],
+        children: vec![ics::identified(
+            "VFREEBUSY".to_string(),
+            properties,
+            Vec::new(),
+        )],
+    })
+}
+
+fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    TIMEZONE
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+        .map(|date_time| date_time.with_timezone(&Utc))
+        .unwrap_or_else(|| date.and_time(NaiveTime::MIN).and_utc())
+}
+
+fn utc(date_time: DateTime<Utc>) -> String {
+    date_time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::to_freebusy;
+    use crate::testing::events;
+
+    fn busy(events: &[&[&str]], from: (i32, u32, u32), to: (i32, u32, u32)) -> Vec<String> {
+        let date = |(year, month, day)| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        to_freebusy(&self::events(events), date(from), date(to))
+            .lines()
+            .filter_map(|line| line.strip_prefix("FREEBUSY;FBTYPE=BUSY:"))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn merges_overlapping_and_adjacent_events() {
+        let events: &[&[&str]] = &[
+            &["DTSTART:20251020T080000Z", "DTEND:20251020T100000Z"],
+            // Overlaps the first event
+            &["DTSTART:20251020T090000Z", "DTEND:20251020T110000Z"],
+            // Directly follows the second event
+            &["DTSTART:20251020T110000Z", "DTEND:20251020T120000Z"],
+            // Contained in the merged period
+            &["DTSTART:20251020T083000Z", "DTEND:20251020T084500Z"],
+            // Separated by a gap
+            &["DTSTART:20251020T121500Z", "DTEND:20251020T130000Z"],
+        ];
+        assert_eq!(
+            busy(events, (2025, 10, 20), (2025, 10, 20)),
+            [
+                "20251020T080000Z/20251020T120000Z",
+                "20251020T121500Z/20251020T130000Z"
+            ]
+        );
+    }
+
+    #[test]
+    fn clips_to_the_range_and_skips_free_events() {
+        let events: &[&[&str]] = &[
+            // 00:00 in Berlin is 22:00 UTC the day before
+            &["DTSTART:20251019T210000Z", "DTEND:20251019T230000Z"],
+            &["DTSTART:20251021T210000Z", "DTEND:20251021T230000Z"],
+            &[
+                "DTSTART:20251020T080000Z",
+                "DTEND:20251020T100000Z",
+                "STATUS:CANCELLED",
+            ],
+            &[
+                "DTSTART:20251020T120000Z",
+                "DTEND:20251020T140000Z",
+                "TRANSP:TRANSPARENT",
+            ],
+            &["DTSTART:20251020T150000Z"],
+        ];
+        assert_eq!(
+            busy(events, (2025, 10, 20), (2025, 10, 21)),
+            [
+                "20251019T220000Z/20251019T230000Z",
+                "20251021T210000Z/20251021T220000Z"
+            ]
+        );
+    }
+
+    #[test]
+    fn output_only_depends_on_the_events() {
+        let events: &[&[&str]] = &[&[
+            "DTSTAMP:20251001T120000Z",
+            "DTSTART:20251020T080000Z",
+            "DTEND:20251020T100000Z",
+        ]];
+        let date = |day| NaiveDate::from_ymd_opt(2025, 10, day).unwrap();
+        let first = to_freebusy(&self::events(events), date(1), date(31));
+        let second = to_freebusy(&self::events(events), date(1), date(31));
+
+        assert_eq!(first, second);
+        assert!(first.contains("DTSTAMP:20251001T120000Z"), "{}", first);
+    }
+
+    #[test]
+    fn recurring_events_are_busy_on_each_instance() {
+        let events: &[&[&str]] = &[&[
+            "DTSTART;TZID=Europe/Berlin:20251020T100000",
+            "DTEND;TZID=Europe/Berlin:20251020T120000",
+            "RRULE:FREQ=WEEKLY;COUNT=3",
+        ]];
+        assert_eq!(
+            busy(events, (2025, 10, 21), (2025, 12, 31)),
+            [
+                "20251027T090000Z/20251027T110000Z",
+                "20251103T090000Z/20251103T110000Z"
+            ]
+        );
+    }
+}
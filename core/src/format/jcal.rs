@@ -0,0 +1,95 @@
+//! jCal, the JSON format for iCalendar (RFC 7265).
+
+use serde_json::{json, Map, Value as Json};
+
+use crate::format::value::{TypedProperty, Value};
+use crate::ics::Node;
+
+/// Recurrence rule parts with integer values.
+const NUMERIC_RULE_PARTS: &[&str] = &[
+    "count",
+    "interval",
+    "bysecond",
+    "byminute",
+    "byhour",
+    "bymonthday",
+    "byyearday",
+    "byweekno",
+    "bymonth",
+    "bysetpos",
+];
+
+/// Serializes a calendar to jCal.
+pub fn to_jcal(calendar: &Node) -> String {
+    serde_json::to_string(&component(calendar)).expect("Could not serialize calendar")
+}
+
+fn component(node: &Node) -> Json {
+    let properties = node
+        .properties
+        .iter()
+        .map(|property| property_json(&TypedProperty::new(property)))
+        .collect::<Vec<_>>();
+    let components = node.children.iter().map(component).collect::<Vec<_>>();
+
+    json!([node.kind.to_lowercase(), properties, components])
+}
+
+fn property_json(property: &TypedProperty) -> Json {
+    let parameters = property
+        .parameters
+        .iter()
+        .map(|(name, value)| (name.clone(), Json::from(value.as_str())))
+        .collect::<Map<_, _>>();
+
+    let mut array = vec![
+        Json::from(property.name.as_str()),
+        Json::Object(parameters),
+        Json::from(property.typ),
+    ];
+    array.extend(property.values.iter().map(value_json));
+
+    Json::Array(array)
+}
+
+fn value_json(value: &Value) -> Json {
+    match value {
+        Value::Text(text) => json!(text),
+        Value::Integer(integer) => json!(integer),
+        Value::Float(float) => json!(float),
+        Value::Boolean(boolean) => json!(boolean),
+        Value::Geo(latitude, longitude) => json!([latitude, longitude]),
+        Value::Period {
+            start,
+            end,
+            duration,
+        } => match end.as_ref().or(duration.as_ref()) {
+            Some(end) => json!(format!("{}/{}", start, end)),
+            None => json!(start),
+        },
+        Value::Recur(parts) => {
+            let parts = parts
+                .iter()
+                .map(|(name, values)| {
+                    let mut values = values
+                        .iter()
+                        .map(|value| match value.parse::<i64>() {
+                            Ok(number) if NUMERIC_RULE_PARTS.contains(&name.as_str()) => {
+                                json!(number)
+                            }
+                            _ => json!(value),
+                        })
+                        .collect::<Vec<_>>();
+                    let value = if values.len() == 1 {
+                        values.remove(0)
+                    } else {
+                        Json::Array(values)
+                    };
+                    (name.clone(), value)
+                })
+                .collect::<Map<_, _>>();
+
+            Json::Object(parts)
+        }
+    }
+}
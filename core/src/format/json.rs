@@ -0,0 +1,105 @@
+//! JSON array of the transformed events.
+//!
+//! ```json
+//! [
+//!   {
+//!     "uid": "...",
+//!     "start": "2024-10-15T10:15:00+02:00",
+//!     "end": "2024-10-15T11:45:00+02:00",
+//!     "all_day": false,
+//!     "summary": "Einführung in die Informatik (IN0001) VO, Standardgruppe",
+//!     "title": "EIDI",
+//!     "course": {
+//!       "name": "Einführung in die Informatik",
+//!       "short_name": "EIDI",
+//!       "ids": ["IN0001"],
+//!       "type": "VO",
+//!       "type_name": "Vorlesung",
+//!       "group": "Standardgruppe",
+//!       "group_number": null,
+//!       "group_day": null,
+//!       "lecturer": null
+//!     },
+//!     "room": "Hörsaal 1 (5602.EG.001)",
+//!     "location": "Boltzmannstr. 3, 85748 Garching b. München"
+//!   }
+//! ]
+//! ```
+//!
+//! `summary` is the summary as exported by CAMPUSonline, `title` the one written to the
+//! calendar. `course` is `null` if the summary has an unknown format, `room` is only set if it
+//! could be mapped to the address in `location`. Times are in local time with offset.
+//! `group_number`, `group_day` (German weekday name) and `lecturer` are only set if the group
+//! contains them.
+
+use icalendar::{Component, EventLike};
+use serde::Serialize;
+
+use crate::event::{Course, Event};
+use crate::event_type::EventType;
+use crate::format::weekday_name;
+
+#[derive(Serialize)]
+struct JsonEvent<'a> {
+    uid: Option<&'a str>,
+    start: Option<String>,
+    end: Option<String>,
+    all_day: bool,
+    summary: &'a str,
+    title: Option<&'a str>,
+    course: Option<JsonCourse<'a>>,
+    room: Option<&'a str>,
+    location: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct JsonCourse<'a> {
+    name: &'a str,
+    short_name: &'a str,
+    ids: &'a [String],
+    #[serde(rename = "type")]
+    typ: EventType,
+    type_name: String,
+    group: &'a str,
+    group_number: Option<u32>,
+    group_day: Option<&'static str>,
+    lecturer: Option<&'a str>,
+}
+
+impl<'a> From<&'a Event> for JsonEvent<'a> {
+    fn from(event: &'a Event) -> Self {
+        Self {
+            uid: event.inner.get_uid(),
+            start: event.start().map(|start| start.to_rfc3339()),
+            end: event.end().map(|end| end.to_rfc3339()),
+            all_day: event.is_all_day(),
+            summary: &event.summary,
+            title: event.inner.get_summary(),
+            course: event.course.as_ref().map(JsonCourse::from),
+            room: event.room.as_deref(),
+            location: event.inner.get_location(),
+        }
+    }
+}
+
+impl<'a> From<&'a Course> for JsonCourse<'a> {
+    fn from(course: &'a Course) -> Self {
+        Self {
+            name: &course.full_name,
+            short_name: &course.short_name,
+            ids: &course.ids,
+            typ: course.typ,
+            type_name: course.typ.to_string(),
+            group: &course.group.name,
+            group_number: course.group.number,
+            group_day: course.group.day.map(weekday_name),
+            lecturer: course.group.lecturer.as_deref(),
+        }
+    }
+}
+
+/// Serializes the events to a JSON array.
+pub fn to_json(events: &[Event]) -> String {
+    let events = events.iter().map(JsonEvent::from).collect::<Vec<_>>();
+    serde_json::to_string(&events).expect("Could not serialize events")
+}
@@ -0,0 +1,128 @@
+//! Output formats other than iCalendar.
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone, Weekday};
+use chrono_tz::Tz;
+
+use crate::event::Event;
+use crate::recurrence;
+use crate::TIMEZONE;
+
+mod agenda;
+mod bundle;
+mod csv;
+mod freebusy;
+mod jcal;
+mod json;
+mod org;
+mod text;
+mod timetable;
+mod value;
+mod xcal;
+
+pub use agenda::to_agenda;
+pub use bundle::to_zip;
+pub use csv::to_csv;
+pub use freebusy::to_freebusy;
+pub use jcal::to_jcal;
+pub use json::to_json;
+pub use org::to_org;
+pub use text::{to_markdown, to_text};
+pub use timetable::to_timetable;
+pub use xcal::to_xcal;
+
+/// German name of the weekday.
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Montag",
+        Weekday::Tue => "Dienstag",
+        Weekday::Wed => "Mittwoch",
+        Weekday::Thu => "Donnerstag",
+        Weekday::Fri => "Freitag",
+        Weekday::Sat => "Samstag",
+        Weekday::Sun => "Sonntag",
+    }
+}
+
+/// Escapes text for use in XML and HTML.
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The Monday of the week containing `date`.
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(date.weekday().num_days_from_monday().into())
+}
+
+/// The events starting in the week beginning on `monday`, sorted by start.
+///
+/// Recurring events are replaced by their instances in the week.
+pub(crate) fn week_events(events: &[Event], monday: NaiveDate) -> Vec<Event> {
+    let next_monday = monday + Days::new(7);
+    let in_week = |event: &Event| {
+        event
+            .start()
+            .map(|start| start.date_naive())
+            .is_some_and(|day| day >= monday && day < next_monday)
+    };
+
+    let (Some(from), Some(to)) = (local_midnight(monday), local_midnight(next_monday)) else {
+        return Vec::new();
+    };
+    let mut events = events
+        .iter()
+        .filter(|event| event.is_recurring() || in_week(event))
+        .flat_map(|event| recurrence::occurrences(event, from, to))
+        .filter(|event| in_week(event))
+        .collect::<Vec<_>>();
+    events.sort_by_key(|event| event.start());
+
+    events
+}
+
+fn local_midnight(date: NaiveDate) -> Option<DateTime<Tz>> {
+    TIMEZONE
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::week_events;
+    use crate::testing::events;
+
+    #[test]
+    fn week_events_expand_recurring_events() {
+        let events = events(&[
+            &[
+                "SUMMARY:Once",
+                "DTSTART:20251105T080000Z",
+                "DTEND:20251105T100000Z",
+            ],
+            &[
+                "SUMMARY:Weekly",
+                "DTSTART;TZID=Europe/Berlin:20251020T100000",
+                "DTEND;TZID=Europe/Berlin:20251020T120000",
+                "RRULE:FREQ=WEEKLY;BYDAY=MO,FR",
+            ],
+            &["SUMMARY:Other week", "DTSTART:20251113T080000Z"],
+        ]);
+        let monday = NaiveDate::from_ymd_opt(2025, 11, 3).unwrap();
+
+        let week = week_events(&events, monday)
+            .iter()
+            .map(|event| {
+                let start = event.start().unwrap().format("%a %H:%M");
+                format!("{} {}", event.summary, start)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            week,
+            ["Weekly Mon 10:00", "Once Wed 09:00", "Weekly Fri 10:00"]
+        );
+    }
+}
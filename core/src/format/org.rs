@@ -0,0 +1,112 @@
+//! Emacs org-mode outline with one heading per event, for org-agenda.
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Datelike, Days};
+use chrono_tz::Tz;
+use icalendar::{Component, EventLike};
+
+use crate::event::Event;
+use crate::format::weekday_name;
+
+/// Serializes the events to org-mode, sorted by start.
+///
+/// Headings are tagged with the course IDs and carry the location and event type as
+/// properties, followed by an active timestamp range and the description as body.
+pub fn to_org(events: &[Event]) -> String {
+    let mut events = events.iter().collect::<Vec<_>>();
+    events.sort_by_key(|event| event.start());
+
+    let mut out = String::new();
+    for event in events {
+        let title = event.inner.get_summary().unwrap_or(&event.summary);
+        let tags = event
+            .course
+            .as_ref()
+            .filter(|course| !course.ids.is_empty())
+            .map(|course| format!(" :{}:", course.ids.join(":")))
+            .unwrap_or_default();
+        writeln!(out, "* {}{}", title.replace(['\r', '\n'], " "), tags)
+            .expect("Could not write to string");
+
+        // The drawer must directly follow the heading, or org-mode doesn't see the properties
+        let mut properties = Vec::new();
+        if let Some(location) = event.inner.get_location() {
+            properties.push(("LOCATION", location.replace(['\r', '\n'], " ")));
+        }
+        if let Some(room) = &event.room {
+            properties.push(("ROOM", room.clone()));
+        }
+        if let Some(course) = &event.course {
+            properties.push(("TYPE", course.typ.to_string()));
+        }
+        if !properties.is_empty() {
+            out.push_str(":PROPERTIES:\n");
+            for (name, value) in properties {
+                writeln!(out, ":{}: {}", name, value).expect("Could not write to string");
+            }
+            out.push_str(":END:\n");
+        }
+
+        if let Some(timestamp) = timestamp(event) {
+            writeln!(out, "{}", timestamp).expect("Could not write to string");
+        }
+
+        if let Some(description) = event.inner.get_description() {
+            for line in description.lines() {
+                // Lines starting with `*` would be read as headings
+                if line.starts_with('*') {
+                    out.push(',');
+                }
+                writeln!(out, "{}", line).expect("Could not write to string");
+            }
+        }
+    }
+
+    out
+}
+
+/// `<2025-10-20 Mo 10:00-12:00>`, or a range of two timestamps for events spanning days.
+fn timestamp(event: &Event) -> Option<String> {
+    let start = event.start()?;
+    let Some(end) = event.end() else {
+        return Some(format!("<{}>", stamp(&start, !event.is_all_day())));
+    };
+
+    if event.is_all_day() {
+        // The end of all-day events is exclusive
+        let last_day = end - Days::new(1);
+        if last_day.date_naive() <= start.date_naive() {
+            return Some(format!("<{}>", stamp(&start, false)));
+        }
+        return Some(format!(
+            "<{}>--<{}>",
+            stamp(&start, false),
+            stamp(&last_day, false)
+        ));
+    }
+
+    if start.date_naive() == end.date_naive() {
+        Some(format!("<{}-{}>", stamp(&start, true), end.format("%H:%M")))
+    } else {
+        Some(format!(
+            "<{}>--<{}>",
+            stamp(&start, true),
+            stamp(&end, true)
+        ))
+    }
+}
+
+fn stamp(time: &DateTime<Tz>, with_time: bool) -> String {
+    let day = &weekday_name(time.weekday())[..2];
+    if with_time {
+        format!(
+            "{} {} {}",
+            time.format("%Y-%m-%d"),
+            day,
+            time.format("%H:%M")
+        )
+    } else {
+        format!("{} {}", time.format("%Y-%m-%d"), day)
+    }
+}
@@ -0,0 +1,154 @@
+//! Compact plain-text and Markdown agendas of a single week, for terminals and chat.
+
+use std::fmt::Write;
+
+use chrono::{Datelike, Days, NaiveDate};
+use icalendar::{Component, EventLike};
+
+use crate::event::Event;
+use crate::format::{week_events, week_start, weekday_name};
+
+/// A line of the agenda, with the parsed fields of the event.
+struct Row {
+    time: String,
+    name: String,
+    typ: String,
+    room: String,
+}
+
+/// Renders the events of the week containing `week` as aligned plain text.
+pub fn to_text(events: &[Event], week: NaiveDate) -> String {
+    let days = rows_by_day(events, week);
+    let rows = days.iter().flat_map(|(_, rows)| rows);
+    let time_width = rows.clone().map(|row| row.time.chars().count()).max();
+    let name_width = rows.clone().map(|row| row.name.chars().count()).max();
+    let typ_width = rows.map(|row| row.typ.chars().count()).max();
+
+    let mut out = String::new();
+    writeln!(out, "{}", heading(week)).expect("Could not write to string");
+    if days.is_empty() {
+        out.push_str("\nKeine Termine\n");
+    }
+    for (day, rows) in &days {
+        writeln!(
+            out,
+            "\n{}, {}",
+            weekday_name(day.weekday()),
+            day.format("%d.%m.%Y")
+        )
+        .expect("Could not write to string");
+        for row in rows {
+            let line = format!(
+                "  {:<time$}  {:<name$}  {:<typ$}  {}",
+                row.time,
+                row.name,
+                row.typ,
+                row.room,
+                time = time_width.unwrap_or_default(),
+                name = name_width.unwrap_or_default(),
+                typ = typ_width.unwrap_or_default(),
+            );
+            writeln!(out, "{}", line.trim_end()).expect("Could not write to string");
+        }
+    }
+
+    out
+}
+
+/// Renders the events of the week containing `week` as Markdown, with a table per day.
+pub fn to_markdown(events: &[Event], week: NaiveDate) -> String {
+    let days = rows_by_day(events, week);
+
+    let mut out = String::new();
+    writeln!(out, "# {}", heading(week)).expect("Could not write to string");
+    if days.is_empty() {
+        out.push_str("\nKeine Termine\n");
+    }
+    for (day, rows) in &days {
+        writeln!(
+            out,
+            "\n## {}, {}\n",
+            weekday_name(day.weekday()),
+            day.format("%d.%m.%Y")
+        )
+        .expect("Could not write to string");
+        out.push_str("| Zeit | Kurs | Typ | Raum |\n");
+        out.push_str("| --- | --- | --- | --- |\n");
+        for row in rows {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                escape_markdown(&row.time),
+                escape_markdown(&row.name),
+                escape_markdown(&row.typ),
+                escape_markdown(&row.room)
+            )
+            .expect("Could not write to string");
+        }
+    }
+
+    out
+}
+
+fn heading(week: NaiveDate) -> String {
+    let monday = week_start(week);
+    format!(
+        "KW {}: {} – {}",
+        monday.iso_week().week(),
+        monday.format("%d.%m."),
+        (monday + Days::new(6)).format("%d.%m.%Y")
+    )
+}
+
+/// The rows of all days with events, in order.
+fn rows_by_day(events: &[Event], week: NaiveDate) -> Vec<(NaiveDate, Vec<Row>)> {
+    let mut days: Vec<(NaiveDate, Vec<Row>)> = Vec::new();
+    for event in week_events(events, week_start(week)) {
+        let Some(start) = event.start() else {
+            continue;
+        };
+        let day = start.date_naive();
+
+        let time = match event.end() {
+            _ if event.is_all_day() => "ganztägig".to_string(),
+            Some(end) => format!("{}–{}", start.format("%H:%M"), end.format("%H:%M")),
+            None => start.format("%H:%M").to_string(),
+        };
+        let row = Row {
+            time,
+            name: event
+                .inner
+                .get_summary()
+                .unwrap_or(&event.summary)
+                .to_string(),
+            typ: event
+                .course
+                .as_ref()
+                .map(|course| course.typ.id().to_string())
+                .unwrap_or_default(),
+            room: event
+                .room
+                .clone()
+                .or_else(|| {
+                    event
+                        .inner
+                        .get_location()
+                        .map(|location| location.to_string())
+                })
+                .unwrap_or_default(),
+        };
+
+        match days.last_mut() {
+            Some((last_day, rows)) if *last_day == day => rows.push(row),
+            _ => days.push((day, vec![row])),
+        }
+    }
+
+    days
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
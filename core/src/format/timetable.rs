@@ -0,0 +1,264 @@
+//! Printable SVG timetable of a single week.
+//!
+//! Monday to Friday are shown as columns, hours as rows. Events overlapping each other are
+//! placed side by side, all-day and weekend events are left out.
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Datelike, NaiveDate, Timelike};
+use chrono_tz::Tz;
+use icalendar::Component;
+
+use crate::event::Event;
+use crate::event_type::EventType;
+use crate::format::{escape, week_events, week_start, weekday_name};
+
+const WIDTH: f64 = 1000.0;
+const HEADER_HEIGHT: f64 = 40.0;
+const TIME_WIDTH: f64 = 50.0;
+const HOUR_HEIGHT: f64 = 48.0;
+const DAYS: u64 = 5;
+/// Hours that are always shown, even if there are no events.
+const FIRST_HOUR: u32 = 8;
+const LAST_HOUR: u32 = 18;
+
+struct Block<'a> {
+    event: &'a Event,
+    start: DateTime<Tz>,
+    end: DateTime<Tz>,
+    column: usize,
+    columns: usize,
+}
+
+/// Renders the events of the week containing `week` as an SVG timetable.
+pub fn to_timetable(events: &[Event], week: NaiveDate) -> String {
+    let monday = week_start(week);
+    let events = week_events(events, monday);
+
+    let days = monday
+        .iter_days()
+        .take(DAYS as usize)
+        .map(|day| layout_day(&events, day))
+        .collect::<Vec<_>>();
+
+    let first_hour = days
+        .iter()
+        .flatten()
+        .map(|block| block.start.hour())
+        .min()
+        .unwrap_or(FIRST_HOUR)
+        .min(FIRST_HOUR);
+    let last_hour = days
+        .iter()
+        .flatten()
+        .map(|block| block.end.hour() + u32::from(block.end.minute() > 0))
+        .max()
+        .unwrap_or(LAST_HOUR)
+        .max(LAST_HOUR);
+
+    let day_width = (WIDTH - TIME_WIDTH) / DAYS as f64;
+    let height = HEADER_HEIGHT + f64::from(last_hour - first_hour) * HOUR_HEIGHT;
+
+    let mut out = String::new();
+    write!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{height}" viewBox="0 0 {WIDTH} {height}" font-family="sans-serif" font-size="12">"#
+    )
+    .expect("Could not write to string");
+    write!(
+        out,
+        r##"<rect width="{WIDTH}" height="{height}" fill="#ffffff"/>"##
+    )
+    .expect("Could not write to string");
+
+    for (i, day) in monday.iter_days().take(DAYS as usize).enumerate() {
+        let x = TIME_WIDTH + i as f64 * day_width;
+        write!(
+            out,
+            r#"<text x="{}" y="{}" text-anchor="middle" font-weight="bold">{} {}</text>"#,
+            x + day_width / 2.0,
+            HEADER_HEIGHT / 2.0 + 5.0,
+            &weekday_name(day.weekday())[..2],
+            day.format("%d.%m.")
+        )
+        .expect("Could not write to string");
+        write!(
+            out,
+            r##"<line x1="{x}" y1="0" x2="{x}" y2="{height}" stroke="#cccccc"/>"##
+        )
+        .expect("Could not write to string");
+    }
+
+    for hour in first_hour..=last_hour {
+        let y = HEADER_HEIGHT + f64::from(hour - first_hour) * HOUR_HEIGHT;
+        write!(
+            out,
+            r##"<line x1="0" y1="{y}" x2="{WIDTH}" y2="{y}" stroke="#cccccc"/>"##
+        )
+        .expect("Could not write to string");
+        if hour < last_hour {
+            write!(
+                out,
+                r#"<text x="{}" y="{}" text-anchor="end">{:02}:00</text>"#,
+                TIME_WIDTH - 6.0,
+                y + 14.0,
+                hour
+            )
+            .expect("Could not write to string");
+        }
+    }
+
+    for (i, blocks) in days.iter().enumerate() {
+        let day_x = TIME_WIDTH + i as f64 * day_width;
+        for block in blocks {
+            let width = day_width / block.columns as f64;
+            let x = day_x + block.column as f64 * width;
+            let y = HEADER_HEIGHT + hours_since(first_hour, &block.start) * HOUR_HEIGHT;
+            let height = (hours_since(first_hour, &block.end)
+                - hours_since(first_hour, &block.start))
+                * HOUR_HEIGHT;
+            push_block(&mut out, block, x, y, width, height);
+        }
+    }
+
+    out.push_str("</svg>\n");
+
+    out
+}
+
+/// Assigns every event of the day a column within its group of overlapping events.
+fn layout_day(events: &[Event], day: NaiveDate) -> Vec<Block<'_>> {
+    let mut blocks = events
+        .iter()
+        .filter(|event| !event.is_all_day())
+        .filter_map(|event| {
+            let start = event.start()?;
+            let end = event.end().unwrap_or(start);
+            (start.date_naive() == day).then_some(Block {
+                event,
+                start,
+                end: if end.date_naive() == day {
+                    end
+                } else {
+                    start
+                        .with_hour(23)
+                        .and_then(|end| end.with_minute(59))
+                        .unwrap_or(start)
+                },
+                column: 0,
+                columns: 1,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut group_start = 0;
+    let mut group_end = None;
+    let mut column_ends: Vec<DateTime<Tz>> = Vec::new();
+    for i in 0..blocks.len() {
+        if group_end.is_some_and(|end| blocks[i].start >= end) {
+            finish_group(&mut blocks[group_start..i], column_ends.len());
+            group_start = i;
+            group_end = None;
+            column_ends.clear();
+        }
+
+        let column = match column_ends.iter().position(|&end| end <= blocks[i].start) {
+            Some(column) => column,
+            None => {
+                column_ends.push(blocks[i].end);
+                column_ends.len() - 1
+            }
+        };
+        column_ends[column] = blocks[i].end;
+        blocks[i].column = column;
+        group_end =
+            Some(group_end.map_or(blocks[i].end, |end: DateTime<Tz>| end.max(blocks[i].end)));
+    }
+    let columns = column_ends.len();
+    finish_group(&mut blocks[group_start..], columns);
+
+    blocks
+}
+
+fn finish_group(blocks: &mut [Block], columns: usize) {
+    for block in blocks {
+        block.columns = columns.max(1);
+    }
+}
+
+fn hours_since(first_hour: u32, time: &DateTime<Tz>) -> f64 {
+    f64::from(time.hour()) + f64::from(time.minute()) / 60.0 - f64::from(first_hour)
+}
+
+fn push_block(out: &mut String, block: &Block, x: f64, y: f64, width: f64, height: f64) {
+    let color = block
+        .event
+        .course
+        .as_ref()
+        .map(|course| color(course.typ))
+        .unwrap_or("#9e9e9e");
+    let title = block
+        .event
+        .course
+        .as_ref()
+        .map(|course| course.short_name.as_str())
+        .or(block.event.inner.get_summary())
+        .unwrap_or(&block.event.summary);
+
+    // Nested SVG elements clip their content, so long names don't overflow the block
+    write!(
+        out,
+        r#"<svg x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}">"#,
+        x + 1.0,
+        y + 1.0,
+        (width - 2.0).max(0.0),
+        (height - 2.0).max(0.0)
+    )
+    .expect("Could not write to string");
+    write!(
+        out,
+        r#"<rect width="100%" height="100%" rx="4" fill="{}"/>"#,
+        color
+    )
+    .expect("Could not write to string");
+
+    let mut lines = vec![(title.to_string(), true)];
+    lines.push((
+        format!(
+            "{} – {}",
+            block.start.format("%H:%M"),
+            block.end.format("%H:%M")
+        ),
+        false,
+    ));
+    if let Some(course) = &block.event.course {
+        lines.push((course.typ.to_string(), false));
+    }
+    if let Some(room) = &block.event.room {
+        lines.push((room.clone(), false));
+    }
+
+    for (i, (line, bold)) in lines.iter().enumerate() {
+        write!(
+            out,
+            r#"<text x="4" y="{}" fill="white"{}>{}</text>"#,
+            14 + i * 14,
+            if *bold { r#" font-weight="bold""# } else { "" },
+            escape(line)
+        )
+        .expect("Could not write to string");
+    }
+
+    out.push_str("</svg>");
+}
+
+fn color(typ: EventType) -> &'static str {
+    match typ {
+        EventType::Vorlesung | EventType::VorlesungMitIntegriertenUebungen => "#3070b3",
+        EventType::Uebung | EventType::Tutorium | EventType::Repetitorium => "#2e7d32",
+        EventType::Seminar | EventType::Hauptseminar | EventType::Proseminar => "#6a1b9a",
+        EventType::Praktikum | EventType::Forschungspraktikum | EventType::Projekt => "#e37222",
+        EventType::Fachpruefung | EventType::Pruefungseinsicht => "#c62828",
+        _ => "#607d8b",
+    }
+}
@@ -0,0 +1,615 @@
+//! Typed property values, shared by jCal and xCal.
+
+use icalendar::{Property, ValueType};
+
+use crate::text;
+
+/// Common extension properties with TEXT values.
+///
+/// `icalendar` doesn't know their type, so their values are kept escaped.
+const TEXT_EXTENSIONS: &[&str] = &["X-WR-CALNAME", "X-WR-CALDESC", "X-WR-TIMEZONE"];
+
+/// A property with its values split and converted as required by RFC 6321 and RFC 7265.
+pub struct TypedProperty {
+    /// Lowercase property name.
+    pub name: String,
+    /// Lowercase parameter names with their values, without `VALUE`.
+    pub parameters: Vec<(String, String)>,
+    /// Name of the value type, e.g. `date-time`.
+    pub typ: &'static str,
+    pub values: Vec<Value>,
+}
+
+pub enum Value {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Geo(f64, f64),
+    Period {
+        start: String,
+        end: Option<String>,
+        duration: Option<String>,
+    },
+    /// Rule parts with their lowercase names and values.
+    Recur(Vec<(String, Vec<String>)>),
+}
+
+impl TypedProperty {
+    pub fn new(property: &Property) -> Self {
+        let value = property.value();
+        let value_type = property.value_type();
+        let typ = match value_type {
+            // DATE-TIME is only the default for these properties
+            Some(ValueType::DateTime) if !value.contains('T') => "date",
+            Some(value_type) => type_name(value_type),
+            None if TEXT_EXTENSIONS.contains(&property.key()) => "text",
+            None => "unknown",
+        };
+
+        let mut parameters = property
+            .params()
+            .values()
+            .filter(|parameter| parameter.key() != "VALUE")
+            .map(|parameter| {
+                (
+                    parameter.key().to_lowercase(),
+                    parameter.value().to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        parameters.sort();
+
+        let values = match (property.key(), typ) {
+            ("GEO", _) => {
+                let mut parts = value.split(';').map(|part| part.trim().parse().ok());
+                match (parts.next().flatten(), parts.next().flatten()) {
+                    (Some(latitude), Some(longitude)) => vec![Value::Geo(latitude, longitude)],
+                    _ => vec![Value::Text(value.to_string())],
+                }
+            }
+            (_, "recur") => vec![recur(value)],
+            (key, _) if text::LIST_PROPERTIES.contains(&key) => text::split_list(value)
+                .into_iter()
+                .map(Value::Text)
+                .collect(),
+            (key, "text") if TEXT_EXTENSIONS.contains(&key) => {
+                vec![Value::Text(text::unescape(value))]
+            }
+            (_, "text" | "unknown") => vec![Value::Text(value.to_string())],
+            (_, typ) => value.split(',').map(|value| convert(typ, value)).collect(),
+        };
+
+        Self {
+            name: property.key().to_lowercase(),
+            parameters,
+            typ,
+            values,
+        }
+    }
+}
+
+fn type_name(value_type: ValueType) -> &'static str {
+    match value_type {
+        ValueType::Binary => "binary",
+        ValueType::Boolean => "boolean",
+        ValueType::CalAddress => "cal-address",
+        ValueType::Date => "date",
+        ValueType::DateTime => "date-time",
+        ValueType::Duration => "duration",
+        ValueType::Float => "float",
+        ValueType::Integer => "integer",
+        ValueType::Period => "period",
+        ValueType::Recur => "recur",
+        ValueType::Text => "text",
+        ValueType::Time => "time",
+        ValueType::Uri => "uri",
+        ValueType::UtcOffset => "utc-offset",
+    }
+}
+
+fn convert(typ: &str, value: &str) -> Value {
+    match typ {
+        "integer" => value
+            .parse()
+            .map(Value::Integer)
+            .unwrap_or_else(|_| Value::Text(value.to_string())),
+        "float" => value
+            .parse()
+            .map(Value::Float)
+            .unwrap_or_else(|_| Value::Text(value.to_string())),
+        "boolean" => Value::Boolean(value.eq_ignore_ascii_case("TRUE")),
+        "period" => {
+            let (start, end) = value.split_once('/').unwrap_or((value, ""));
+            let start = date_time(start);
+            if end.starts_with(['P', '+', '-']) {
+                Value::Period {
+                    start,
+                    end: None,
+                    duration: Some(end.to_string()),
+                }
+            } else {
+                Value::Period {
+                    start,
+                    end: Some(date_time(end)),
+                    duration: None,
+                }
+            }
+        }
+        _ => Value::Text(format_text(typ, value)),
+    }
+}
+
+fn format_text(typ: &str, value: &str) -> String {
+    match typ {
+        "date" => date(value),
+        "date-time" => date_time(value),
+        "time" => time(value),
+        "utc-offset" => utc_offset(value),
+        _ => value.to_string(),
+    }
+}
+
+fn recur(value: &str) -> Value {
+    let parts = value
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(name, values)| {
+            let name = name.to_lowercase();
+            let values = if name == "until" {
+                vec![format_text(
+                    if values.contains('T') {
+                        "date-time"
+                    } else {
+                        "date"
+                    },
+                    values,
+                )]
+            } else {
+                values.split(',').map(|value| value.to_string()).collect()
+            };
+            (name, values)
+        })
+        .collect();
+
+    Value::Recur(parts)
+}
+
+/// `20251020` to `2025-10-20`.
+fn date(value: &str) -> String {
+    if value.len() == 8 && value.is_ascii() {
+        format!("{}-{}-{}", &value[..4], &value[4..6], &value[6..])
+    } else {
+        value.to_string()
+    }
+}
+
+/// `20251020T101500Z` to `2025-10-20T10:15:00Z`.
+fn date_time(value: &str) -> String {
+    match value.split_once('T') {
+        Some((d, t)) => format!("{}T{}", date(d), time(t)),
+        None => date(value),
+    }
+}
+
+/// `101500Z` to `10:15:00Z`.
+fn time(value: &str) -> String {
+    if value.len() >= 6 && value.is_ascii() {
+        format!(
+            "{}:{}:{}{}",
+            &value[..2],
+            &value[2..4],
+            &value[4..6],
+            &value[6..]
+        )
+    } else {
+        value.to_string()
+    }
+}
+
+/// `+0100` to `+01:00`.
+fn utc_offset(value: &str) -> String {
+    if !value.is_ascii() {
+        return value.to_string();
+    }
+    match value.len() {
+        5 => format!("{}:{}", &value[..3], &value[3..]),
+        7 => format!("{}:{}:{}", &value[..3], &value[3..5], &value[5..]),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ical::parser::ical::component::IcalTimeZoneTransitionType;
+    use ical::property::Property as IcalProperty;
+    use ical::IcalParser;
+    use roxmltree::{Document, Node as XmlNode};
+    use serde_json::{json, Map, Value as Json};
+
+    use crate::format::{to_jcal, to_xcal};
+    use crate::ics::{self, Node};
+    use crate::pipeline::Pipeline;
+    use crate::{text, transform};
+
+    /// A property as `(component path, name, parameters, value)` in iCalendar syntax.
+    type Line = (String, String, Vec<(String, String)>, String);
+
+    const TIMEZONE: &[&str] = &[
+        "BEGIN:VTIMEZONE",
+        "TZID:Europe/Berlin",
+        "BEGIN:DAYLIGHT",
+        "DTSTART:19700329T020000",
+        "TZOFFSETFROM:+0100",
+        "TZOFFSETTO:+0200",
+        "RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU",
+        "END:DAYLIGHT",
+        "BEGIN:STANDARD",
+        "DTSTART:19701025T030000",
+        "TZOFFSETFROM:+0200",
+        "TZOFFSETTO:+0100",
+        "END:STANDARD",
+        "END:VTIMEZONE",
+    ];
+
+    /// Calendar properties and components, and event properties and components.
+    const CASES: &[(&[&str], &[&str])] = &[
+        (&[], &["DTSTART;VALUE=DATE:20251020", "DTEND:20251021"]),
+        (
+            &[],
+            &["DTSTAMP:20251001T081500Z", "LAST-MODIFIED:20251002T120000Z"],
+        ),
+        (
+            TIMEZONE,
+            &[
+                "DTSTART;TZID=Europe/Berlin:20251020T100000",
+                "DTEND;TZID=Europe/Berlin:20251020T120000",
+            ],
+        ),
+        (
+            &[],
+            &["RDATE;VALUE=PERIOD:20251020T080000Z/20251020T100000Z"],
+        ),
+        (&[], &["RDATE;VALUE=PERIOD:20251020T080000Z/PT2H"]),
+        (
+            &[],
+            &[
+                "DTSTART:20251020T080000Z",
+                "RRULE:FREQ=WEEKLY;COUNT=14;INTERVAL=2;BYDAY=MO,WE",
+                "EXDATE:20251027T080000Z",
+            ],
+        ),
+        (&[], &["RRULE:FREQ=WEEKLY;BYDAY=TU;UNTIL=20260131T230000Z"]),
+        (
+            &[],
+            &["GEO:48.262;11.668", "SEQUENCE:3", "DURATION:PT1H30M"],
+        ),
+        (&[], &[r"CATEGORIES:Vorlesung,Pflicht\, Informatik"]),
+        (
+            &[
+                r"X-WR-CALNAME:Mein Kalender\, TUM",
+                "X-WR-TIMEZONE:Europe/Berlin",
+            ],
+            &[
+                r"DESCRIPTION:Komma\, Semikolon\; Zeile\nZwei",
+                "LOCATION:MW 0001",
+            ],
+        ),
+        (&[], &[r"X-CAMPUS-INFO:a\,b", "URL:https://campus.tum.de/"]),
+        (
+            &[],
+            &[
+                "ATTENDEE;ROLE=REQ-PARTICIPANT:mailto:a@tum.de",
+                "BEGIN:VALARM",
+                "ACTION:DISPLAY",
+                "TRIGGER:-PT15M",
+                "DESCRIPTION:Erinnerung",
+                "END:VALARM",
+            ],
+        ),
+    ];
+
+    /// Runs a calendar with the given properties and components, and an event with the given
+    /// properties and components, through the transformation, as all output formats do.
+    fn calendar(calendar: &[&str], event: &[&str]) -> Node {
+        let mut lines = vec!["BEGIN:VCALENDAR", "VERSION:2.0"];
+        lines.extend(calendar);
+        lines.extend(["BEGIN:VEVENT", "UID:test", "SUMMARY:Test"]);
+        lines.extend(event);
+        lines.extend(["END:VEVENT", "END:VCALENDAR", ""]);
+
+        let parsed = IcalParser::new(lines.join("\r\n").as_bytes())
+            .next()
+            .expect("No calendar")
+            .expect("Invalid calendar");
+        let transformed = transform(parsed, &Pipeline::new()).expect("Transform failed");
+        ics::tree(transformed.inner(), transformed.events())
+    }
+
+    fn from_ics(ics: &str) -> Vec<Line> {
+        let calendar = IcalParser::new(ics.as_bytes())
+            .next()
+            .expect("No calendar")
+            .expect("Invalid calendar");
+
+        let mut lines = Vec::new();
+        let mut push = |path: &str, properties: &[IcalProperty]| {
+            for property in properties {
+                let parameters = property
+                    .params
+                    .iter()
+                    .flatten()
+                    .filter(|(name, _)| name != "VALUE")
+                    .map(|(name, values)| (name.to_lowercase(), values.join(",")))
+                    .collect();
+                let value = property.value.clone().unwrap_or_default();
+                lines.push(line(path, &property.name, parameters, value));
+            }
+        };
+
+        push("vcalendar", &calendar.properties);
+        for event in &calendar.events {
+            push("vcalendar/vevent", &event.properties);
+            for alarm in &event.alarms {
+                push("vcalendar/vevent/valarm", &alarm.properties);
+            }
+        }
+        for timezone in &calendar.timezones {
+            push("vcalendar/vtimezone", &timezone.properties);
+            for transition in &timezone.transitions {
+                let path = match transition.transition {
+                    IcalTimeZoneTransitionType::STANDARD => "vcalendar/vtimezone/standard",
+                    IcalTimeZoneTransitionType::DAYLIGHT => "vcalendar/vtimezone/daylight",
+                };
+                push(path, &transition.properties);
+            }
+        }
+
+        lines.sort();
+        lines
+    }
+
+    fn from_jcal(jcal: &str) -> Vec<Line> {
+        let mut lines = Vec::new();
+        jcal_component(
+            &serde_json::from_str(jcal).expect("Invalid JSON"),
+            "",
+            &mut lines,
+        );
+
+        lines.sort();
+        lines
+    }
+
+    fn jcal_component(component: &Json, path: &str, lines: &mut Vec<Line>) {
+        let [kind, properties, components] =
+            component.as_array().expect("Invalid component").as_slice()
+        else {
+            panic!("Invalid component {}", component);
+        };
+        let path = format!("{}{}", path, kind.as_str().expect("Invalid component name"));
+
+        for property in properties.as_array().expect("Invalid properties") {
+            let property = property.as_array().expect("Invalid property");
+            let parameters = property[1]
+                .as_object()
+                .expect("Invalid parameters")
+                .iter()
+                .map(|(name, value)| (name.clone(), value.as_str().unwrap_or_default().to_string()))
+                .collect();
+            let name = property[0].as_str().expect("Invalid property name");
+            let typ = property[2].as_str().expect("Invalid value type");
+            lines.push(line(
+                &path,
+                name,
+                parameters,
+                ics_value(typ, &property[3..]),
+            ));
+        }
+
+        for child in components.as_array().expect("Invalid components") {
+            jcal_component(child, &format!("{}/", path), lines);
+        }
+    }
+
+    fn from_xcal(xcal: &str) -> Vec<Line> {
+        let document = Document::parse(xcal).expect("Invalid XML");
+        let calendar = elements(document.root_element())
+            .next()
+            .expect("No calendar");
+
+        let mut lines = Vec::new();
+        xcal_component(calendar, "", &mut lines);
+
+        lines.sort();
+        lines
+    }
+
+    fn xcal_component(component: XmlNode, path: &str, lines: &mut Vec<Line>) {
+        let path = format!("{}{}", path, component.tag_name().name());
+
+        for child in elements(component) {
+            match child.tag_name().name() {
+                "properties" => {
+                    for property in elements(child) {
+                        lines.push(xcal_property(property, &path));
+                    }
+                }
+                "components" => {
+                    for component in elements(child) {
+                        xcal_component(component, &format!("{}/", path), lines);
+                    }
+                }
+                name => panic!("Unexpected element {}", name),
+            }
+        }
+    }
+
+    /// Collects the property in the shape of jCal, so both share the conversion.
+    fn xcal_property(property: XmlNode, path: &str) -> Line {
+        let mut parameters = Vec::new();
+        let mut typ = None;
+        let mut values = Vec::new();
+        for value in elements(property) {
+            let name = value.tag_name().name();
+            match name {
+                "parameters" => {
+                    for parameter in elements(value) {
+                        let value = elements(parameter).next().expect("Missing parameter value");
+                        parameters.push((
+                            parameter.tag_name().name().to_string(),
+                            value.text().unwrap_or_default().to_string(),
+                        ));
+                    }
+                }
+                "latitude" => {
+                    typ = Some("float");
+                    values.push(json!([number(value), Json::Null]));
+                }
+                "longitude" => {
+                    let geo = values.last_mut().expect("Longitude without latitude");
+                    geo[1] = number(value);
+                }
+                "period" => {
+                    typ = Some("period");
+                    let parts = elements(value)
+                        .map(|part| part.text().unwrap_or_default())
+                        .collect::<Vec<_>>();
+                    values.push(json!(parts.join("/")));
+                }
+                "recur" => {
+                    typ = Some("recur");
+                    let mut parts = Map::new();
+                    for part in elements(value) {
+                        let values = parts
+                            .entry(part.tag_name().name())
+                            .or_insert_with(|| json!([]));
+                        values
+                            .as_array_mut()
+                            .expect("Invalid rule part")
+                            .push(json!(part.text().unwrap_or_default()));
+                    }
+                    values.push(Json::Object(parts));
+                }
+                _ => {
+                    typ = Some(typ.unwrap_or(name));
+                    values.push(json!(value.text().unwrap_or_default()));
+                }
+            }
+        }
+
+        let typ = typ.expect("Property without value");
+        line(
+            path,
+            property.tag_name().name(),
+            parameters,
+            ics_value(typ, &values),
+        )
+    }
+
+    fn elements<'a, 'input>(
+        node: XmlNode<'a, 'input>,
+    ) -> impl Iterator<Item = XmlNode<'a, 'input>> {
+        node.children().filter(|child| child.is_element())
+    }
+
+    fn number(node: XmlNode) -> Json {
+        let number = node.text().unwrap_or_default().parse::<f64>();
+        json!(number.expect("Invalid number"))
+    }
+
+    /// Converts jCal values back to the iCalendar syntax.
+    fn ics_value(typ: &str, values: &[Json]) -> String {
+        let values = values
+            .iter()
+            .map(|value| match value {
+                Json::String(value) => match typ {
+                    "date" | "date-time" | "time" | "utc-offset" => ics_date_time(value),
+                    "period" => {
+                        let (start, end) = value.split_once('/').expect("Invalid period");
+                        let end = if end.starts_with(['P', '+', '-']) {
+                            end.to_string()
+                        } else {
+                            ics_date_time(end)
+                        };
+                        format!("{}/{}", ics_date_time(start), end)
+                    }
+                    "text" => text::escape(value),
+                    "boolean" => value.to_uppercase(),
+                    _ => value.clone(),
+                },
+                Json::Number(number) => number.to_string(),
+                Json::Bool(boolean) => boolean.to_string().to_uppercase(),
+                Json::Array(geo) => format!("{};{}", geo[0], geo[1]),
+                Json::Object(parts) => parts
+                    .iter()
+                    .map(|(name, values)| {
+                        let values = match values {
+                            Json::Array(values) => values.iter().collect(),
+                            value => vec![value],
+                        };
+                        let values = values
+                            .iter()
+                            .map(|value| match value {
+                                Json::String(value) if name == "until" => ics_date_time(value),
+                                Json::String(value) => value.clone(),
+                                value => value.to_string(),
+                            })
+                            .collect::<Vec<_>>();
+                        format!("{}={}", name.to_uppercase(), values.join(","))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(";"),
+                Json::Null => panic!("Null value"),
+            })
+            .collect::<Vec<_>>();
+
+        values.join(",")
+    }
+
+    fn ics_date_time(value: &str) -> String {
+        value.replace(['-', ':'], "")
+    }
+
+    fn line(path: &str, name: &str, mut parameters: Vec<(String, String)>, value: String) -> Line {
+        let name = name.to_uppercase();
+        parameters.sort();
+
+        // The order of rule parts is not kept in jCal and xCal
+        let value = if name.ends_with("RULE") {
+            let mut parts = value.split(';').collect::<Vec<_>>();
+            parts.sort();
+            parts.join(";")
+        } else {
+            value
+        };
+
+        (path.to_lowercase(), name, parameters, value)
+    }
+
+    #[test]
+    fn round_trip() {
+        for (calendar_lines, event_lines) in CASES {
+            let node = calendar(calendar_lines, event_lines);
+            let ics = ics::write(&node);
+            for line in calendar_lines.iter().chain(event_lines.iter()) {
+                assert!(ics.contains(line), "{} is missing in {}", line, ics);
+            }
+
+            let expected = from_ics(&ics);
+            assert_eq!(from_jcal(&to_jcal(&node)), expected, "jCal of {}", ics);
+            assert_eq!(from_xcal(&to_xcal(&node)), expected, "xCal of {}", ics);
+        }
+    }
+
+    #[test]
+    fn periods_are_strings() {
+        let node = calendar(&[], &["RDATE;VALUE=PERIOD:20251020T080000Z/PT2H"]);
+        let jcal = to_jcal(&node);
+        assert!(
+            jcal.contains(r#"["rdate",{},"period","2025-10-20T08:00:00Z/PT2H"]"#),
+            "{}",
+            jcal
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! xCal, the XML format for iCalendar (RFC 6321).
+
+use std::fmt::Write;
+
+use crate::format::escape;
+use crate::format::value::{TypedProperty, Value};
+use crate::ics::Node;
+
+/// Serializes a calendar to xCal.
+pub fn to_xcal(calendar: &Node) -> String {
+    let mut out = String::new();
+    out.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    out.push_str(r#"<icalendar xmlns="urn:ietf:params:xml:ns:icalendar-2.0">"#);
+    push_component(&mut out, calendar);
+    out.push_str("</icalendar>");
+
+    out
+}
+
+fn push_component(out: &mut String, node: &Node) {
+    let kind = node.kind.to_lowercase();
+    write!(out, "<{}><properties>", kind).expect("Could not write to string");
+    for property in &node.properties {
+        push_property(out, &TypedProperty::new(property));
+    }
+    out.push_str("</properties>");
+
+    if !node.children.is_empty() {
+        out.push_str("<components>");
+        for child in &node.children {
+            push_component(out, child);
+        }
+        out.push_str("</components>");
+    }
+    write!(out, "</{}>", kind).expect("Could not write to string");
+}
+
+fn push_property(out: &mut String, property: &TypedProperty) {
+    write!(out, "<{}>", property.name).expect("Could not write to string");
+
+    if !property.parameters.is_empty() {
+        out.push_str("<parameters>");
+        for (name, value) in &property.parameters {
+            let typ = match name.as_str() {
+                "altrep" | "dir" => "uri",
+                "delegated-from" | "delegated-to" | "member" | "sent-by" => "cal-address",
+                _ => "text",
+            };
+            write!(out, "<{name}><{typ}>{}</{typ}></{name}>", escape(value))
+                .expect("Could not write to string");
+        }
+        out.push_str("</parameters>");
+    }
+
+    for value in &property.values {
+        push_value(out, property.typ, value);
+    }
+
+    write!(out, "</{}>", property.name).expect("Could not write to string");
+}
+
+fn push_value(out: &mut String, typ: &str, value: &Value) {
+    match value {
+        Value::Text(text) => push_element(out, typ, text),
+        Value::Integer(integer) => push_element(out, typ, &integer.to_string()),
+        Value::Float(float) => push_element(out, typ, &float.to_string()),
+        Value::Boolean(boolean) => push_element(out, typ, &boolean.to_string()),
+        Value::Geo(latitude, longitude) => {
+            push_element(out, "latitude", &latitude.to_string());
+            push_element(out, "longitude", &longitude.to_string());
+        }
+        Value::Period {
+            start,
+            end,
+            duration,
+        } => {
+            out.push_str("<period>");
+            push_element(out, "start", start);
+            if let Some(end) = end {
+                push_element(out, "end", end);
+            }
+            if let Some(duration) = duration {
+                push_element(out, "duration", duration);
+            }
+            out.push_str("</period>");
+        }
+        Value::Recur(parts) => {
+            out.push_str("<recur>");
+            for (name, values) in parts {
+                for value in values {
+                    push_element(out, name, value);
+                }
+            }
+            out.push_str("</recur>");
+        }
+    }
+}
+
+fn push_element(out: &mut String, name: &str, text: &str) {
+    write!(out, "<{name}>{}</{name}>", escape(text)).expect("Could not write to string");
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+
+use chrono::Weekday;
+use lazy_regex::regex;
+use regex::Regex;
+
+use crate::event::normalize_name;
+
+/// The group of an event, e.g. `Gruppe 03 Mo (Müller)`.
+///
+/// Number, day and lecturer are only set if the group text contains them, CAMPUSonline doesn't
+/// use a fixed format for it.
+#[derive(Clone, Debug, Default)]
+pub struct Group {
+    /// The group as listed after the event type, e.g. `Standardgruppe`.
+    pub name: String,
+    /// The number of numbered groups, e.g. `3` for `Gruppe 03` or `12` for `Tutorübung 12`.
+    pub number: Option<u32>,
+    /// The weekday the group meets on.
+    pub day: Option<Weekday>,
+    /// The lecturer or tutor of the group.
+    pub lecturer: Option<String>,
+}
+
+impl Group {
+    pub fn parse(text: &str) -> Self {
+        let number_reg: &Regex =
+            regex!(r"(?i)(?:^|gruppe\s*|\bgr\.\s*|\bnr\.\s*)0*(?<number>[0-9]+)\b");
+        // Any other short number on its own, e.g. `Tutorübung 12, Do`, but not times or rooms
+        let other_number_reg: &Regex = regex!(r"(?:^|[\s(,])0*(?<number>[0-9]{1,3})(?:$|[\s),;])");
+        let day_reg: &Regex = regex!(
+            r"\b(?<day>Montag|Dienstag|Mittwoch|Donnerstag|Freitag|Samstag|Sonntag|Mo|Di|Mi|Do|Fr|Sa|So)\b"
+        );
+        let lecturer_reg: &Regex = regex!(
+            r"(?:\b(?:bei|Leitung:?|Tutor(?:in)?:?)\s+(?<named>[^,()]+)|\((?<parens>[^()0-9]+)\))"
+        );
+
+        let name = text.trim().to_string();
+        let number = number_reg
+            .captures(&name)
+            .or_else(|| other_number_reg.captures(&name))
+            .and_then(|captures| captures["number"].parse().ok());
+        let day = day_reg
+            .captures(&name)
+            .and_then(|captures| parse_day(&captures["day"]));
+        let lecturer = lecturer_reg.captures(&name).and_then(|captures| {
+            let lecturer = captures.name("named").or(captures.name("parens"))?;
+            Some(lecturer.as_str().trim().to_string()).filter(|lecturer| !lecturer.is_empty())
+        });
+
+        Self {
+            name,
+            number,
+            day,
+            lecturer,
+        }
+    }
+
+    /// Whether the key is the group number or the (normalized) group name.
+    pub fn matches(&self, key: &str) -> bool {
+        match key.trim().parse::<u32>() {
+            Ok(number) => self.number == Some(number),
+            Err(_) => normalize_name(&self.name) == normalize_name(key),
+        }
+    }
+}
+
+fn parse_day(day: &str) -> Option<Weekday> {
+    match day.get(..2)? {
+        "Mo" => Some(Weekday::Mon),
+        "Di" => Some(Weekday::Tue),
+        "Mi" => Some(Weekday::Wed),
+        "Do" => Some(Weekday::Thu),
+        "Fr" => Some(Weekday::Fri),
+        "Sa" => Some(Weekday::Sat),
+        "So" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Weekday;
+
+    use super::Group;
+
+    #[test]
+    fn parses_group_texts() {
+        let cases = [
+            ("Standardgruppe", None, None, None),
+            ("Zentralübung", None, None, None),
+            ("Gruppe 03", Some(3), None, None),
+            (
+                "Gruppe 03 Mo (Müller)",
+                Some(3),
+                Some(Weekday::Mon),
+                Some("Müller"),
+            ),
+            ("Tutorübung 12, Do", Some(12), Some(Weekday::Thu), None),
+            (
+                "Übungsgruppe 5 bei Max Mustermann",
+                Some(5),
+                None,
+                Some("Max Mustermann"),
+            ),
+            ("Gr. 04 Fr", Some(4), Some(Weekday::Fri), None),
+            (
+                "Tutorgruppe 07 - Mittwoch 10:00-12:00",
+                Some(7),
+                Some(Weekday::Wed),
+                None,
+            ),
+            ("Mittwoch 10:00-12:00", None, Some(Weekday::Wed), None),
+            ("Übung (MI 00.13.009A)", None, None, None),
+            ("Gruppe A, Di", None, Some(Weekday::Tue), None),
+            (
+                "Nr. 2 Leitung: Anna Schmidt",
+                Some(2),
+                None,
+                Some("Anna Schmidt"),
+            ),
+            ("Tutorium 2025", None, None, None),
+        ];
+        for (text, number, day, lecturer) in cases {
+            let group = Group::parse(text);
+            assert_eq!(group.name, text);
+            assert_eq!(group.number, number, "{}", text);
+            assert_eq!(group.day, day, "{}", text);
+            assert_eq!(group.lecturer.as_deref(), lecturer, "{}", text);
+        }
+    }
+
+    #[test]
+    fn matches_number_or_name() {
+        let group = Group::parse("Tutorübung 12, Do");
+        assert!(group.matches("12"));
+        assert!(group.matches(" 012 "));
+        assert!(group.matches("tutorübung 12 do"));
+        assert!(!group.matches("1"));
+        assert!(!group.matches("Tutorübung"));
+
+        let group = Group::parse("Standardgruppe");
+        assert!(group.matches("standardgruppe"));
+        assert!(!group.matches("0"));
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::Write;
+
+use icalendar::{Calendar as iCalendar, CalendarComponent, Component, Property};
+use sha2::{Digest, Sha256};
+
+use crate::event::Event;
+use crate::text;
+
+/// A component as it is written by all output formats.
+pub struct Node {
+    pub kind: String,
+    pub properties: Vec<Property>,
+    pub children: Vec<Node>,
+}
+
+/// Collects the calendar with all of its components into a `VCALENDAR` node.
+///
+/// `icalendar` adds `DTSTAMP` and `UID` to every component it writes, which is invalid for
+/// e.g. `VTIMEZONE` and `VALARM`, so only events and to-dos get them here.
+pub fn tree(calendar: &iCalendar, events: &[Event]) -> Node {
+    let mut children = Vec::new();
+    for component in &calendar.components {
+        match component {
+            CalendarComponent::Event(event) => children.push(node(event, true)),
+            CalendarComponent::Todo(todo) => children.push(node(todo, true)),
+            CalendarComponent::Venue(venue) => children.push(node(venue, false)),
+            CalendarComponent::Other(other) => children.push(node(other, false)),
+            _ => {}
+        }
+    }
+    for event in events {
+        children.push(node(&event.inner, true));
+    }
+
+    Node {
+        kind: "VCALENDAR".to_string(),
+        properties: calendar.properties.clone(),
+        children,
+    }
+}
+
+fn node<C: Component>(component: &C, needs_identity: bool) -> Node {
+    let properties = component
+        .properties()
+        .values()
+        .chain(component.multi_properties().values().flatten())
+        .cloned()
+        .collect::<Vec<_>>();
+    let children = component
+        .components()
+        .iter()
+        .map(|child| node(child, false))
+        .collect();
+
+    if needs_identity {
+        identified(component.component_kind(), properties, children)
+    } else {
+        Node {
+            kind: component.component_kind(),
+            properties,
+            children,
+        }
+    }
+}
+
+/// Builds a node for a component that needs `DTSTAMP` and `UID`, adding them if missing.
+pub fn identified(kind: String, mut properties: Vec<Property>, children: Vec<Node>) -> Node {
+    let has = |key: &str| properties.iter().any(|property| property.key() == key);
+
+    let mut identity = Vec::new();
+    if !has("DTSTAMP") {
+        let now = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+        identity.push(Property::new("DTSTAMP", now.to_string()));
+    }
+    if !has("UID") {
+        // Derived from the content, so clients recognize the component on the next refresh
+        let mut content = String::new();
+        for property in &properties {
+            push_property(&mut content, property);
+        }
+        let uid = Sha256::digest(content.as_bytes());
+        identity.push(Property::new("UID", format!("{:x}@tum-cal-proxy", uid)));
+    }
+    identity.append(&mut properties);
+
+    Node {
+        kind,
+        properties: identity,
+        children,
+    }
+}
+
+/// Serializes a calendar to iCalendar text.
+pub fn to_ics(calendar: &iCalendar, events: &[Event]) -> String {
+    write(&tree(calendar, events))
+}
+
+/// Serializes a component with its children to iCalendar text.
+pub fn write(node: &Node) -> String {
+    let mut out = String::new();
+    push_node(&mut out, node);
+
+    out
+}
+
+fn push_property(out: &mut String, property: &Property) {
+    if text::LIST_PROPERTIES.contains(&property.key()) {
+        // `icalendar` would escape the commas between the values, which are already escaped
+        push_folded(out, &raw_line(property));
+        return;
+    }
+
+    let line: String = property
+        .clone()
+        .try_into()
+        .expect("Could not write to string");
+    out.push_str(&line);
+}
+
+fn raw_line(property: &Property) -> String {
+    let mut line = property.key().to_string();
+    for parameter in property.params().values() {
+        let value = parameter.value();
+        if value.contains([':', ';', ',']) && !value.starts_with('"') {
+            write!(line, ";{}=\"{}\"", parameter.key(), value)
+        } else {
+            write!(line, ";{}={}", parameter.key(), value)
+        }
+        .expect("Could not write to string");
+    }
+    line.push(':');
+    line.push_str(property.value());
+
+    line
+}
+
+/// Writes a content line, folded after 75 octets, see RFC 5545, section 3.1.
+fn push_folded(out: &mut String, line: &str) {
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            length = 1;
+        }
+        out.push(c);
+        length += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn push_node(out: &mut String, node: &Node) {
+    write!(out, "BEGIN:{}\r\n", node.kind).expect("Could not write to string");
+    for property in &node.properties {
+        push_property(out, property);
+    }
+    for child in &node.children {
+        push_node(out, child);
+    }
+    write!(out, "END:{}\r\n", node.kind).expect("Could not write to string");
+}
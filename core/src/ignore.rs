@@ -0,0 +1,231 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::event::Course;
+
+/// Maximum number of glob and regex entries per ignore list.
+pub const MAX_PATTERNS: usize = 16;
+/// Maximum length of a single glob or regex entry.
+pub const MAX_PATTERN_LENGTH: usize = 256;
+/// Maximum size of a compiled pattern, so that patterns can't use up memory and CPU time.
+const PATTERN_SIZE_LIMIT: usize = 1 << 16;
+
+/// Courses whose events are removed.
+///
+/// Plain entries match the full course name or a course ID exactly. Entries prefixed with
+/// `glob:` (`*` and `?` as wildcards, case-insensitive) or `re:` (a regular expression) match
+/// the full name, course IDs, group or room.
+#[derive(Clone, Debug, Default)]
+pub struct IgnoreList {
+    exact: HashSet<String>,
+    patterns: Vec<Regex>,
+}
+
+#[derive(Debug)]
+pub enum InvalidIgnoreEntry {
+    TooManyPatterns,
+    TooLong(String),
+    InvalidPattern(String),
+}
+
+impl IgnoreList {
+    pub fn parse(entries: impl IntoIterator<Item = String>) -> Result<Self, InvalidIgnoreEntry> {
+        let mut list = Self::default();
+        for entry in entries {
+            let pattern = if let Some(glob) = entry.strip_prefix("glob:") {
+                glob_to_regex(glob)
+            } else if let Some(regex) = entry.strip_prefix("re:") {
+                regex.to_string()
+            } else {
+                list.exact.insert(entry);
+                continue;
+            };
+
+            if list.patterns.len() >= MAX_PATTERNS {
+                return Err(InvalidIgnoreEntry::TooManyPatterns);
+            }
+            if entry.len() > MAX_PATTERN_LENGTH {
+                return Err(InvalidIgnoreEntry::TooLong(entry));
+            }
+            let regex = RegexBuilder::new(&pattern)
+                .size_limit(PATTERN_SIZE_LIMIT)
+                .dfa_size_limit(PATTERN_SIZE_LIMIT)
+                .build()
+                .map_err(|_| InvalidIgnoreEntry::InvalidPattern(entry))?;
+            list.patterns.push(regex);
+        }
+
+        Ok(list)
+    }
+
+    /// Whether the events of the course, taking place in the given room, are ignored.
+    pub fn matches(&self, course: &Course, room: Option<&str>) -> bool {
+        if self.exact.contains(&course.full_name)
+            || course.ids.iter().any(|id| self.exact.contains(id))
+        {
+            return true;
+        }
+
+        self.patterns.iter().any(|pattern| {
+            pattern.is_match(&course.full_name)
+                || course.ids.iter().any(|id| pattern.is_match(id))
+                || pattern.is_match(&course.group.name)
+                || room.is_some_and(|room| pattern.is_match(room))
+        })
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("(?i)^");
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+
+    regex
+}
+
+impl fmt::Display for InvalidIgnoreEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyPatterns => write!(
+                f,
+                "Too many ignore patterns, at most {} are allowed",
+                MAX_PATTERNS
+            ),
+            Self::TooLong(entry) => write!(
+                f,
+                "Ignore pattern is longer than {} characters: {}",
+                MAX_PATTERN_LENGTH, entry
+            ),
+            Self::InvalidPattern(entry) => write!(f, "Invalid ignore pattern: {}", entry),
+        }
+    }
+}
+
+impl std::error::Error for InvalidIgnoreEntry {}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_to_regex, IgnoreList, InvalidIgnoreEntry, MAX_PATTERNS, MAX_PATTERN_LENGTH};
+    use crate::event::Course;
+    use crate::event_type::EventType;
+    use crate::group::Group;
+
+    fn course(full_name: &str, ids: &[&str], group: &str) -> Course {
+        Course {
+            full_name: full_name.to_string(),
+            short_name: full_name.to_string(),
+            ids: ids.iter().map(|id| id.to_string()).collect(),
+            typ: EventType::Uebung,
+            group: Group::parse(group),
+        }
+    }
+
+    fn list(entries: &[&str]) -> IgnoreList {
+        IgnoreList::parse(entries.iter().map(|entry| entry.to_string())).expect("Invalid list")
+    }
+
+    #[test]
+    fn globs_to_regexes() {
+        assert_eq!(glob_to_regex("IN*"), "(?i)^IN.*$");
+        assert_eq!(glob_to_regex("MA09?2"), "(?i)^MA09.2$");
+        assert_eq!(glob_to_regex("a.b (c)+"), r"(?i)^a\.b \(c\)\+$");
+        assert_eq!(glob_to_regex(""), "(?i)^$");
+    }
+
+    #[test]
+    fn exact_entries() {
+        let analysis = course("Analysis für Informatik", &["MA0902"], "Standardgruppe");
+        assert!(list(&["Analysis für Informatik"]).matches(&analysis, None));
+        assert!(list(&["MA0902"]).matches(&analysis, None));
+        assert!(!list(&["analysis für informatik"]).matches(&analysis, None));
+        assert!(!list(&["MA09*"]).matches(&analysis, None));
+        assert!(!list(&["Standardgruppe"]).matches(&analysis, None));
+    }
+
+    #[test]
+    fn glob_entries() {
+        let analysis = course("Analysis für Informatik", &["MA0902"], "Standardgruppe");
+        assert!(list(&["glob:ma09*"]).matches(&analysis, None));
+        assert!(list(&["glob:analysis*"]).matches(&analysis, None));
+        assert!(list(&["glob:MA09?2"]).matches(&analysis, None));
+        // Globs match the whole text
+        assert!(!list(&["glob:MA09"]).matches(&analysis, None));
+        assert!(!list(&["glob:Analysis"]).matches(&analysis, None));
+        // Regex syntax is literal in globs
+        assert!(!list(&["glob:MA09.2"]).matches(&analysis, None));
+    }
+
+    #[test]
+    fn regex_entries() {
+        let analysis = course("Analysis für Informatik", &["MA0902"], "Standardgruppe");
+        assert!(list(&["re:^MA\\d{4}$"]).matches(&analysis, None));
+        assert!(list(&["re:für"]).matches(&analysis, None));
+        assert!(!list(&["re:^IN"]).matches(&analysis, None));
+        assert!(!list(&["re:analysis"]).matches(&analysis, None));
+        assert!(list(&["re:(?i)analysis"]).matches(&analysis, None));
+    }
+
+    #[test]
+    fn patterns_match_group_and_room() {
+        let exercise = course(
+            "Einführung in die Informatik",
+            &["IN0001"],
+            "Tutorübung 12, Do",
+        );
+        assert!(list(&["glob:Tutorübung*"]).matches(&exercise, None));
+        assert!(list(&["re:, Do$"]).matches(&exercise, None));
+        assert!(list(&["glob:*MW*"]).matches(&exercise, Some("MW 2001, Rudolf-Diesel-Hörsaal")));
+        assert!(!list(&["glob:*MW*"]).matches(&exercise, None));
+        assert!(!list(&["glob:*MW*"]).matches(&exercise, Some("00.02.001")));
+        // Exact entries only match names and IDs
+        assert!(!list(&["Tutorübung 12, Do"]).matches(&exercise, None));
+    }
+
+    #[test]
+    fn limits_patterns() {
+        let patterns = (0..MAX_PATTERNS).map(|i| format!("glob:IN{}*", i));
+        assert!(IgnoreList::parse(patterns.clone()).is_ok());
+
+        let too_many = patterns.chain(["re:MA".to_string()]);
+        assert!(matches!(
+            IgnoreList::parse(too_many),
+            Err(InvalidIgnoreEntry::TooManyPatterns)
+        ));
+
+        // Exact entries aren't limited
+        let exact = (0..MAX_PATTERNS * 2).map(|i| format!("IN{:04}", i));
+        assert!(IgnoreList::parse(exact).is_ok());
+    }
+
+    #[test]
+    fn limits_pattern_length() {
+        let longest = format!("re:{}", "a".repeat(MAX_PATTERN_LENGTH - 3));
+        assert!(IgnoreList::parse([longest.clone()]).is_ok());
+
+        let too_long = format!("{}a", longest);
+        assert!(matches!(
+            IgnoreList::parse([too_long.clone()]),
+            Err(InvalidIgnoreEntry::TooLong(entry)) if entry == too_long
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_and_oversized_regexes() {
+        assert!(matches!(
+            IgnoreList::parse(["re:(".to_string()]),
+            Err(InvalidIgnoreEntry::InvalidPattern(entry)) if entry == "re:("
+        ));
+        assert!(matches!(
+            IgnoreList::parse(["re:\\w{1000}\\w{1000}".to_string()]),
+            Err(InvalidIgnoreEntry::InvalidPattern(_))
+        ));
+    }
+}
@@ -0,0 +1,34 @@
+//! Framework-independent transformation of CAMPUSonline calendars.
+
+mod calendar;
+pub mod data;
+mod date_range;
+mod error;
+mod event;
+pub mod event_type;
+pub mod format;
+mod group;
+mod ics;
+mod ignore;
+pub mod pipeline;
+mod recurrence;
+mod semester;
+#[cfg(test)]
+mod testing;
+pub mod text;
+mod transform;
+mod utils;
+
+pub use calendar::Calendar;
+pub use data::DataSet;
+pub use date_range::{DateExpr, InvalidDateExpr};
+pub use error::TransformError;
+pub use event::{courses, normalize_name, Course, CourseSummary, Event};
+pub use group::Group;
+pub use ignore::{IgnoreList, InvalidIgnoreEntry};
+pub use pipeline::{Options, Pipeline, Stage, StageKind};
+pub use semester::Semester;
+pub use transform::transform;
+
+/// The time zone all local times refer to.
+pub const TIMEZONE: chrono_tz::Tz = chrono_tz::Europe::Berlin;
@@ -0,0 +1,40 @@
+use crate::event::Event;
+use crate::pipeline::Stage;
+
+/// Keeps only the events of a single course, given by its key (see [`crate::Course::key`]),
+/// course ID or name.
+pub struct CourseFilter {
+    course: Option<String>,
+}
+
+impl CourseFilter {
+    /// Keeps all events if no course is given.
+    pub fn new(course: Option<String>) -> Self {
+        Self { course }
+    }
+}
+
+impl Stage for CourseFilter {
+    fn apply(&self, events: &mut Vec<Event>) {
+        let Some(key) = &self.course else {
+            return;
+        };
+
+        // Keys of listed courses are exact, IDs may be shared by several courses
+        let by_key = events.iter().any(|event| {
+            event
+                .course
+                .as_ref()
+                .is_some_and(|course| course.key() == *key)
+        });
+        events.retain(|event| {
+            event.course.as_ref().is_some_and(|course| {
+                if by_key {
+                    course.key() == *key
+                } else {
+                    course.matches(key)
+                }
+            })
+        });
+    }
+}
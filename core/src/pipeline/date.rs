@@ -0,0 +1,143 @@
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::event::Event;
+use crate::pipeline::Stage;
+use crate::recurrence;
+use crate::TIMEZONE;
+
+/// Removes events outside of a date range, keeping those that overlap its boundaries.
+///
+/// Recurring events are kept if any of their instances is in the range.
+pub struct DateFilter {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+impl DateFilter {
+    /// Both days are inclusive, a missing day leaves the range open on that side.
+    pub fn new(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Self {
+        Self { from, to }
+    }
+}
+
+impl Stage for DateFilter {
+    fn apply(&self, events: &mut Vec<Event>) {
+        if self.from.is_none() && self.to.is_none() {
+            return;
+        }
+
+        let start = self.from.and_then(local_midnight);
+        let end = self
+            .to
+            .and_then(|to| to.checked_add_days(Days::new(1)))
+            .and_then(local_midnight);
+
+        events.retain(|event| {
+            let Some(event_start) = event.start() else {
+                return true;
+            };
+            if event.is_recurring() {
+                let from = start.unwrap_or(DateTime::<Utc>::MIN_UTC.with_timezone(&TIMEZONE));
+                let to = end.unwrap_or(DateTime::<Utc>::MAX_UTC.with_timezone(&TIMEZONE));
+                // Rules that can't be expanded only rule out events starting after the range
+                return recurrence::has_instance(event, from, to)
+                    .unwrap_or_else(|| end.is_none_or(|end| event_start < end));
+            }
+            let event_end = event.end().unwrap_or(event_start);
+
+            let after_start = start.is_none_or(|start| event_end > start || event_start >= start);
+            let before_end = end.is_none_or(|end| event_start < end);
+            after_start && before_end
+        });
+    }
+}
+
+fn local_midnight(date: NaiveDate) -> Option<DateTime<Tz>> {
+    TIMEZONE
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::DateFilter;
+    use crate::pipeline::Stage;
+    use crate::testing::events;
+
+    fn filter(from: &str, to: &str, events: &[&[&str]]) -> Vec<String> {
+        let date = |date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+        let mut events = self::events(events);
+        DateFilter::new(date(from), date(to)).apply(&mut events);
+
+        events.into_iter().map(|event| event.summary).collect()
+    }
+
+    #[test]
+    fn keeps_overlapping_events() {
+        let events: &[&[&str]] = &[
+            &[
+                "SUMMARY:Before",
+                "DTSTART:20251019T080000Z",
+                "DTEND:20251019T100000Z",
+            ],
+            &[
+                "SUMMARY:Overlapping",
+                "DTSTART:20251019T200000Z",
+                "DTEND:20251020T010000Z",
+            ],
+            &[
+                "SUMMARY:Inside",
+                "DTSTART:20251021T080000Z",
+                "DTEND:20251021T100000Z",
+            ],
+            &[
+                "SUMMARY:After",
+                "DTSTART:20251025T220000Z",
+                "DTEND:20251025T230000Z",
+            ],
+        ];
+        assert_eq!(
+            filter("2025-10-20", "2025-10-25", events),
+            ["Overlapping", "Inside"]
+        );
+        assert_eq!(filter("", "", events).len(), 4);
+    }
+
+    #[test]
+    fn keeps_recurring_events_with_instances_in_range() {
+        let events: &[&[&str]] = &[
+            &[
+                "SUMMARY:Weekly",
+                "DTSTART:20251006T080000Z",
+                "RRULE:FREQ=WEEKLY",
+            ],
+            &[
+                "SUMMARY:Ended",
+                "DTSTART:20251006T080000Z",
+                "RRULE:FREQ=WEEKLY;COUNT=2",
+            ],
+            &[
+                "SUMMARY:Later",
+                "DTSTART:20251006T080000Z",
+                "RDATE:20251215T090000Z",
+            ],
+            &[
+                "SUMMARY:Unsupported",
+                "DTSTART:20251006T080000Z",
+                "RRULE:FREQ=MONTHLY;BYDAY=1MO",
+            ],
+        ];
+        assert_eq!(
+            filter("2025-11-01", "2025-11-30", events),
+            ["Weekly", "Unsupported"]
+        );
+        assert_eq!(
+            filter("2025-12-01", "", events),
+            ["Weekly", "Later", "Unsupported"]
+        );
+        assert_eq!(filter("", "2025-10-01", events), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,19 @@
+use std::collections::HashSet;
+
+use icalendar::Component;
+
+use crate::event::Event;
+use crate::pipeline::Stage;
+
+/// Removes events with the same summary and start as an earlier one.
+pub struct Dedup;
+
+impl Stage for Dedup {
+    fn apply(&self, events: &mut Vec<Event>) {
+        let mut already_seen = HashSet::new();
+        events.retain(|event| {
+            let dedup_key = format!("{}-{:?}", event.summary, event.inner.get_start());
+            already_seen.insert(dedup_key)
+        });
+    }
+}
@@ -0,0 +1,46 @@
+use std::fmt::Write;
+
+use icalendar::Component;
+
+use crate::event::Event;
+use crate::pipeline::Stage;
+
+/// Lists the parsed details in the description, followed by the original summary and
+/// description.
+pub struct BuildDescription;
+
+impl Stage for BuildDescription {
+    fn apply(&self, events: &mut Vec<Event>) {
+        for event in events {
+            let Some(course) = &event.course else {
+                continue;
+            };
+
+            let mut description = String::new();
+            writeln!(&mut description, "Name: {}", course.full_name)
+                .expect("Could not write to string");
+            writeln!(
+                &mut description,
+                "Typ: {} ({})",
+                course.typ,
+                course.typ.id()
+            )
+            .expect("Could not write to string");
+            if !course.ids.is_empty() {
+                writeln!(&mut description, "IDs: {}", course.ids.join(", "))
+                    .expect("Could not write to string");
+            }
+            if let Some(room) = &event.room {
+                writeln!(&mut description, "Raum: {}", room).expect("Could not write to string");
+            }
+            writeln!(&mut description, "Gruppe: {}", course.group)
+                .expect("Could not write to string");
+            writeln!(&mut description, "\n------------\n\n").expect("Could not write to string");
+            write!(&mut description, "{}", event.summary).expect("Could not write to string");
+            if let Some(desc) = event.inner.get_description() {
+                write!(&mut description, "\n\n{}", desc).expect("Could not write to string");
+            }
+            event.inner.description(description.as_str());
+        }
+    }
+}
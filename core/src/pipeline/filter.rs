@@ -0,0 +1,62 @@
+use icalendar::Component;
+
+use crate::event::Event;
+use crate::event_type::Filter;
+use crate::ignore::IgnoreList;
+use crate::pipeline::Stage;
+
+/// Removes events whose type is not selected by the filter.
+///
+/// Events of courses with their own filter use that one instead of the global filter.
+pub struct TypeFilter {
+    filter: Filter,
+    courses: Vec<(String, Filter)>,
+}
+
+/// Removes events of ignored courses, see [`IgnoreList`].
+pub struct IgnoreFilter {
+    ignore: IgnoreList,
+}
+
+impl TypeFilter {
+    /// Course filters are given by course ID or name, the first matching one is used.
+    pub fn new(filter: Filter, courses: Vec<(String, Filter)>) -> Self {
+        Self { filter, courses }
+    }
+}
+
+impl Stage for TypeFilter {
+    fn apply(&self, events: &mut Vec<Event>) {
+        events.retain(|event| match &event.course {
+            Some(course) => self
+                .courses
+                .iter()
+                .find(|(key, _)| course.matches(key))
+                .map_or(&self.filter, |(_, filter)| filter)
+                .contains(course.typ),
+            None => true,
+        });
+    }
+}
+
+impl IgnoreFilter {
+    pub fn new(ignore: IgnoreList) -> Self {
+        Self { ignore }
+    }
+}
+
+impl Stage for IgnoreFilter {
+    fn apply(&self, events: &mut Vec<Event>) {
+        events.retain(|event| match &event.course {
+            Some(course) => {
+                // Before `MapLocation`, the location is still the room
+                let room = event
+                    .room
+                    .as_deref()
+                    .or_else(|| event.inner.property_value("LOCATION"));
+                !self.ignore.matches(course, room)
+            }
+            None => true,
+        });
+    }
+}
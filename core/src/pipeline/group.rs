@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer};
+
+use crate::event::{normalize_name, Event};
+use crate::event_type::EventType;
+use crate::pipeline::Stage;
+
+/// Keeps only the selected groups of courses, removing the parallel groups.
+///
+/// Only events of the same course and event type as a selected group are removed, so selecting
+/// an exercise group keeps the lectures. Selections that match no group remove nothing.
+pub struct GroupFilter {
+    groups: Vec<GroupSelection>,
+}
+
+/// A group of a course, given as `<course>:<group>`, e.g. `IN0001:3` or `IN0001:Gruppe 03`.
+///
+/// The course is a course ID or name, the group its number or name.
+#[derive(Clone, Debug)]
+pub struct GroupSelection {
+    pub course: String,
+    pub group: String,
+}
+
+#[derive(Debug)]
+pub struct InvalidGroupSelection {
+    selection: String,
+}
+
+impl GroupFilter {
+    /// Keeps all events if no groups are given.
+    pub fn new(groups: Vec<GroupSelection>) -> Self {
+        Self { groups }
+    }
+
+    fn is_selected(&self, event: &Event) -> bool {
+        event.course.as_ref().is_some_and(|course| {
+            self.groups.iter().any(|selection| {
+                course.matches(&selection.course) && course.group.matches(&selection.group)
+            })
+        })
+    }
+}
+
+impl Stage for GroupFilter {
+    fn apply(&self, events: &mut Vec<Event>) {
+        if self.groups.is_empty() {
+            return;
+        }
+
+        // Courses and event types with a selected group, other groups of them are removed
+        let selected: HashSet<(String, EventType)> = events
+            .iter()
+            .filter(|event| self.is_selected(event))
+            .filter_map(|event| event.course.as_ref())
+            .map(|course| (normalize_name(&course.full_name), course.typ))
+            .collect();
+
+        events.retain(|event| {
+            let Some(course) = &event.course else {
+                return true;
+            };
+
+            self.is_selected(event)
+                || !selected.contains(&(normalize_name(&course.full_name), course.typ))
+        });
+    }
+}
+
+impl FromStr for GroupSelection {
+    type Err = InvalidGroupSelection;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((course, group)) if !course.trim().is_empty() && !group.trim().is_empty() => {
+                Ok(Self {
+                    course: course.trim().to_string(),
+                    group: group.trim().to_string(),
+                })
+            }
+            _ => Err(InvalidGroupSelection {
+                selection: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for GroupSelection {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<GroupSelection>()
+            .map_err(|e| de::Error::custom(format!("{}", e)))
+    }
+}
+
+impl fmt::Display for InvalidGroupSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid group: {}, expected <course>:<group>",
+            self.selection
+        )
+    }
+}
+
+impl std::error::Error for InvalidGroupSelection {}
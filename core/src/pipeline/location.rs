@@ -0,0 +1,63 @@
+use icalendar::EventLike;
+use lazy_regex::regex;
+use regex::Regex;
+use tracing::info;
+
+use crate::data::DataSet;
+use crate::event::Event;
+use crate::pipeline::Stage;
+
+/// Replaces the room with the address of its building, keeping the room in `Event::room`.
+pub struct MapLocation<'a> {
+    data: &'a DataSet,
+}
+
+impl<'a> MapLocation<'a> {
+    pub fn new(data: &'a DataSet) -> Self {
+        Self { data }
+    }
+}
+
+impl Stage for MapLocation<'_> {
+    fn apply(&self, events: &mut Vec<Event>) {
+        let room_reg: &Regex = regex!(
+            r#"(?x)
+            \(
+            (?<building_id> \d{4} ) \.
+            (?<floor> \d\d|EG|UG|DG|Z\d|U\d ) \.
+            (?<room_id> [\dA-Z]+ )
+            \)
+        "#
+        );
+
+        events.retain_mut(|event| {
+            if event.course.is_none() {
+                return true;
+            }
+            let Some(loc) = event.inner.get_location().map(|loc| loc.to_string()) else {
+                return true;
+            };
+
+            if let Some(captures) = room_reg.captures(&loc) {
+                let building_id = &captures["building_id"];
+                let building_id: u16 = match building_id.parse() {
+                    Ok(id) => id,
+                    Err(_) => {
+                        info!("Encountered invalid building ID: {}", building_id);
+                        return false;
+                    }
+                };
+                if let Some(address) = self.data.match_building_id(building_id) {
+                    event.inner.location(address);
+                    event.room = Some(loc);
+                } else {
+                    info!("Encountered unknown building ID: {}", building_id)
+                }
+            } else if !loc.starts_with("Online") {
+                info!("Encountered location with unknown format: {}", loc);
+            }
+
+            true
+        });
+    }
+}
@@ -0,0 +1,152 @@
+//! The stages events pass through during the transformation.
+//!
+//! Every stage gets all events at once, so that it can remove, modify or reorder them. The
+//! stages run in the order they were added to the [`Pipeline`].
+
+use std::collections::HashSet;
+
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use serde::Deserialize;
+
+use crate::data::DataSet;
+use crate::event::Event;
+use crate::event_type::Filter;
+use crate::ignore::IgnoreList;
+
+mod course;
+mod date;
+mod dedup;
+mod description;
+mod filter;
+mod group;
+mod location;
+mod parse;
+mod rename;
+mod time;
+
+pub use course::CourseFilter;
+pub use date::DateFilter;
+pub use dedup::Dedup;
+pub use description::BuildDescription;
+pub use filter::{IgnoreFilter, TypeFilter};
+pub use group::{GroupFilter, GroupSelection, InvalidGroupSelection};
+pub use location::MapLocation;
+pub use parse::ParseSummary;
+pub use rename::RenameCourse;
+pub use time::{DayOfWeek, InvalidDayOfWeek, TimeFilter};
+
+/// A single step of the transformation.
+pub trait Stage {
+    fn apply(&self, events: &mut Vec<Event>);
+}
+
+/// The built-in stages, so they can be selected by name.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StageKind {
+    Dedup,
+    ParseSummary,
+    TypeFilter,
+    IgnoreFilter,
+    CourseFilter,
+    GroupFilter,
+    DateFilter,
+    TimeFilter,
+    RenameCourse,
+    MapLocation,
+    BuildDescription,
+}
+
+/// Configuration of the built-in stages.
+pub struct Options<'a> {
+    /// Event types to keep.
+    pub filter: Filter,
+    /// Event types to keep per course ID or name, instead of `filter`.
+    pub course_filters: Vec<(String, Filter)>,
+    /// Courses whose events are removed.
+    pub ignore: IgnoreList,
+    /// Course ID or name of the only course to keep, for per-course feeds.
+    pub course: Option<String>,
+    /// Groups to keep, removing the other groups of their courses.
+    pub groups: Vec<GroupSelection>,
+    /// First day of events to keep.
+    pub from: Option<NaiveDate>,
+    /// Last day of events to keep.
+    pub to: Option<NaiveDate>,
+    /// Weekdays of events to keep.
+    pub weekdays: Option<HashSet<Weekday>>,
+    /// Earliest start of events to keep.
+    pub after: Option<NaiveTime>,
+    /// Latest end of events to keep.
+    pub before: Option<NaiveTime>,
+    /// Building addresses and course name abbreviations to use.
+    pub data: &'a DataSet,
+}
+
+/// An ordered list of stages.
+#[derive(Default)]
+pub struct Pipeline<'a> {
+    stages: Vec<Box<dyn Stage + 'a>>,
+}
+
+impl StageKind {
+    /// The stages used unless a request selects its own.
+    pub const DEFAULT: [StageKind; 11] = [
+        StageKind::Dedup,
+        StageKind::ParseSummary,
+        StageKind::TypeFilter,
+        StageKind::IgnoreFilter,
+        StageKind::CourseFilter,
+        StageKind::GroupFilter,
+        StageKind::DateFilter,
+        StageKind::TimeFilter,
+        StageKind::RenameCourse,
+        StageKind::MapLocation,
+        StageKind::BuildDescription,
+    ];
+}
+
+impl<'a> Pipeline<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a pipeline of built-in stages, in the given order.
+    pub fn from_kinds(kinds: &[StageKind], options: Options<'a>) -> Self {
+        let mut pipeline = Self::new();
+        for kind in kinds {
+            match kind {
+                StageKind::Dedup => pipeline.push(Dedup),
+                StageKind::ParseSummary => pipeline.push(ParseSummary),
+                StageKind::TypeFilter => pipeline.push(TypeFilter::new(
+                    options.filter.clone(),
+                    options.course_filters.clone(),
+                )),
+                StageKind::IgnoreFilter => pipeline.push(IgnoreFilter::new(options.ignore.clone())),
+                StageKind::CourseFilter => pipeline.push(CourseFilter::new(options.course.clone())),
+                StageKind::GroupFilter => pipeline.push(GroupFilter::new(options.groups.clone())),
+                StageKind::DateFilter => pipeline.push(DateFilter::new(options.from, options.to)),
+                StageKind::TimeFilter => pipeline.push(TimeFilter::new(
+                    options.weekdays.clone(),
+                    options.after,
+                    options.before,
+                )),
+                StageKind::RenameCourse => pipeline.push(RenameCourse::new(options.data)),
+                StageKind::MapLocation => pipeline.push(MapLocation::new(options.data)),
+                StageKind::BuildDescription => pipeline.push(BuildDescription),
+            }
+        }
+
+        pipeline
+    }
+
+    pub fn push(&mut self, stage: impl Stage + 'a) {
+        self.stages.push(Box::new(stage));
+    }
+
+    pub fn run(&self, events: &mut Vec<Event>) {
+        for stage in &self.stages {
+            stage.apply(events);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use icalendar::Component;
+use lazy_regex::regex;
+use regex::Regex;
+use tracing::info;
+
+use crate::event::{Course, Event};
+use crate::event_type::EventType;
+use crate::group::Group;
+use crate::pipeline::Stage;
+
+/// Parses course name, IDs, event type and group from the summary.
+///
+/// Events with an unknown format are kept, but without any course details, so later stages
+/// leave them as they are.
+pub struct ParseSummary;
+
+impl Stage for ParseSummary {
+    fn apply(&self, events: &mut Vec<Event>) {
+        for event in events {
+            event.course = parse_course(event);
+        }
+    }
+}
+
+fn parse_course(event: &Event) -> Option<Course> {
+    let name_reg: &Regex = regex!(
+        r#"(?x)
+        (?<name> .*? )
+        \s?
+        (?: [ \(\[ ]
+            (?<id> [A-Z]{2,3}[0-9]+ (?:, \s? [A-Z]{2,3}[0-9]+)* )
+        [ \)\] ] \s? )?
+        (?<tag> [A-Z]{2} ),
+        \s?
+        (?<group> .* )
+    "#
+    );
+    let Some(captures) = name_reg.captures(&event.summary) else {
+        info!("Encountered event with unknown format: {}", event.summary);
+        return None;
+    };
+
+    let mut full_name = captures["name"].trim().to_string();
+    if full_name.ends_with(" -") {
+        full_name.pop();
+        full_name.pop();
+    }
+    if let Some(desc) = event.inner.get_description() {
+        // For some reason this is necessary for some courses
+        if desc.contains("Zentralübung") {
+            full_name.push_str(" - Zentralübung");
+        }
+    }
+
+    let ids = captures
+        .name("id")
+        .map(|id| {
+            id.as_str()
+                .split(",")
+                .map(|id| id.trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let typ = if let Ok(id) = captures["tag"].parse::<EventType>() {
+        id
+    } else {
+        info!("Encountered unknown event type: {}", &captures["tag"]);
+        return None;
+    };
+    let group = Group::parse(&captures["group"]);
+
+    Some(Course {
+        short_name: full_name.clone(),
+        full_name,
+        ids,
+        typ,
+        group,
+    })
+}
@@ -0,0 +1,29 @@
+use icalendar::Component;
+
+use crate::data::DataSet;
+use crate::event::Event;
+use crate::pipeline::Stage;
+
+/// Uses the abbreviated course name as summary.
+pub struct RenameCourse<'a> {
+    data: &'a DataSet,
+}
+
+impl<'a> RenameCourse<'a> {
+    pub fn new(data: &'a DataSet) -> Self {
+        Self { data }
+    }
+}
+
+impl Stage for RenameCourse<'_> {
+    fn apply(&self, events: &mut Vec<Event>) {
+        for event in events {
+            let Some(course) = &mut event.course else {
+                continue;
+            };
+
+            course.short_name = self.data.replace_course_name(course.full_name.clone());
+            event.inner.summary(course.short_name.as_str());
+        }
+    }
+}
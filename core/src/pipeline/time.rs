@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveTime, Weekday};
+use serde::{de, Deserialize, Deserializer};
+
+use crate::event::Event;
+use crate::pipeline::Stage;
+
+/// Removes events on other weekdays or outside of a time window, in local time.
+///
+/// All-day events are only filtered by their weekday.
+pub struct TimeFilter {
+    weekdays: Option<HashSet<Weekday>>,
+    after: Option<NaiveTime>,
+    before: Option<NaiveTime>,
+}
+
+/// A weekday, given by its English name or German abbreviation, e.g. `mon`, `Monday` or `Mo`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct DayOfWeek(pub Weekday);
+
+#[derive(Debug)]
+pub struct InvalidDayOfWeek {
+    day: String,
+}
+
+impl TimeFilter {
+    /// Keeps events on the given weekdays that start no earlier than `after` and end no later
+    /// than `before`, everything that isn't given is not filtered.
+    pub fn new(
+        weekdays: Option<HashSet<Weekday>>,
+        after: Option<NaiveTime>,
+        before: Option<NaiveTime>,
+    ) -> Self {
+        Self {
+            weekdays,
+            after,
+            before,
+        }
+    }
+}
+
+impl Stage for TimeFilter {
+    fn apply(&self, events: &mut Vec<Event>) {
+        if self.weekdays.is_none() && self.after.is_none() && self.before.is_none() {
+            return;
+        }
+
+        events.retain(|event| {
+            let Some(start) = event.start() else {
+                return true;
+            };
+
+            if let Some(weekdays) = &self.weekdays {
+                if !weekdays.contains(&start.weekday()) {
+                    return false;
+                }
+            }
+            if event.is_all_day() {
+                return true;
+            }
+
+            if self.after.is_some_and(|after| start.time() < after) {
+                return false;
+            }
+            if let Some(before) = self.before {
+                let end = event.end().unwrap_or(start);
+                if end.date_naive() != start.date_naive() || end.time() > before {
+                    return false;
+                }
+            }
+
+            true
+        });
+    }
+}
+
+impl FromStr for DayOfWeek {
+    type Err = InvalidDayOfWeek;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let weekday = match s.to_lowercase().as_str() {
+            "mo" => Some(Weekday::Mon),
+            "di" => Some(Weekday::Tue),
+            "mi" => Some(Weekday::Wed),
+            "do" => Some(Weekday::Thu),
+            "fr" => Some(Weekday::Fri),
+            "sa" => Some(Weekday::Sat),
+            "so" => Some(Weekday::Sun),
+            _ => s.parse::<Weekday>().ok(),
+        };
+
+        weekday
+            .map(DayOfWeek)
+            .ok_or_else(|| InvalidDayOfWeek { day: s.to_string() })
+    }
+}
+
+impl<'de> Deserialize<'de> for DayOfWeek {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<DayOfWeek>()
+            .map_err(|e| de::Error::custom(format!("{}", e)))
+    }
+}
+
+impl fmt::Display for InvalidDayOfWeek {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid weekday: {}", self.day)
+    }
+}
+
+impl std::error::Error for InvalidDayOfWeek {}
@@ -0,0 +1,633 @@
+//! Expansion of recurring events (`RRULE`, `RDATE` and `EXDATE`) into their instances.
+//!
+//! Only the rule parts needed for lecture series are supported: `FREQ`, `INTERVAL`, `COUNT`,
+//! `UNTIL`, `WKST` and `BYDAY` of weekly rules. Rules with other parts are not expanded.
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+use chrono_tz::Tz;
+use icalendar::{CalendarDateTime, Component, DatePerhapsTime, EventLike, Property, ValueType};
+
+use crate::event::Event;
+use crate::utils::to_local_time;
+
+/// Rule periods (days, weeks, months or years) that are looked at before giving up.
+const MAX_PERIODS: u32 = 100_000;
+
+struct Instance {
+    start: DatePerhapsTime,
+    end: Option<DatePerhapsTime>,
+}
+
+#[derive(Copy, Clone)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<DateTime<Tz>>,
+    days: Vec<Weekday>,
+    week_start: Weekday,
+}
+
+/// The instances of the event overlapping `from..to` as events of their own, without
+/// recurrence properties.
+///
+/// Returns `None` if the recurrence rule isn't supported.
+pub(crate) fn instances(event: &Event, from: DateTime<Tz>, to: DateTime<Tz>) -> Option<Vec<Event>> {
+    let instances = times(event, to, None)?
+        .into_iter()
+        .filter(|instance| overlaps(instance, from, to))
+        .map(|instance| {
+            let mut inner = event.inner.clone();
+            inner.starts(instance.start);
+            match instance.end {
+                Some(end) => inner.ends(end),
+                None => inner.remove_property("DTEND"),
+            };
+            inner.remove_property("RRULE");
+            inner.remove_multi_property("RDATE");
+            inner.remove_multi_property("EXDATE");
+
+            Event {
+                inner,
+                ..event.clone()
+            }
+        })
+        .collect();
+
+    Some(instances)
+}
+
+/// Whether an instance of the event overlaps `from..to`, `None` if the rule isn't supported.
+pub(crate) fn has_instance(event: &Event, from: DateTime<Tz>, to: DateTime<Tz>) -> Option<bool> {
+    // Later instances don't matter once one starts in the range
+    let instances = times(event, to, Some(from))?;
+    Some(
+        instances
+            .iter()
+            .any(|instance| overlaps(instance, from, to)),
+    )
+}
+
+/// The instances of the event to show for `from..to`: all instances of recurring events, or
+/// the event itself if it doesn't recur or its rule isn't supported.
+pub(crate) fn occurrences(event: &Event, from: DateTime<Tz>, to: DateTime<Tz>) -> Vec<Event> {
+    if !event.is_recurring() {
+        return vec![event.clone()];
+    }
+
+    instances(event, from, to).unwrap_or_else(|| vec![event.clone()])
+}
+
+fn overlaps(instance: &Instance, from: DateTime<Tz>, to: DateTime<Tz>) -> bool {
+    let Some(start) = to_local_time(instance.start.clone()) else {
+        return false;
+    };
+    let end = instance
+        .end
+        .clone()
+        .and_then(to_local_time)
+        .unwrap_or(start);
+
+    (end > from || start >= from) && start < to
+}
+
+/// All instances starting before `to`, sorted by start.
+///
+/// With `enough`, the rule isn't followed past the first instance starting at that time.
+fn times(event: &Event, to: DateTime<Tz>, enough: Option<DateTime<Tz>>) -> Option<Vec<Instance>> {
+    let Some(start) = event.inner.get_start() else {
+        return Some(Vec::new());
+    };
+    let end = event.inner.get_end();
+    let first = naive(&start);
+
+    let mut instances = vec![Instance {
+        start: start.clone(),
+        end: end.clone(),
+    }];
+
+    if let Some(rule) = event.inner.property_value("RRULE") {
+        let rule = Rule::parse(rule, &start)?;
+        let shifted = |date_time: NaiveDateTime| Instance {
+            start: with_naive(&start, date_time),
+            end: end
+                .as_ref()
+                .map(|end| with_naive(end, naive(end) + (date_time - first))),
+        };
+
+        'periods: for period in 1..MAX_PERIODS {
+            for candidate in rule.candidates(first, period - 1) {
+                if candidate <= first {
+                    continue;
+                }
+                // Times skipped by a DST change don't exist
+                let Some(local) = to_local_time(with_naive(&start, candidate)) else {
+                    continue;
+                };
+                if local >= to
+                    || rule.until.is_some_and(|until| local > until)
+                    || rule.count.is_some_and(|count| instances.len() >= count)
+                {
+                    break 'periods;
+                }
+                instances.push(shifted(candidate));
+                if enough.is_some_and(|enough| local >= enough) {
+                    break 'periods;
+                }
+            }
+        }
+    }
+
+    for property in event
+        .inner
+        .multi_properties()
+        .get("RDATE")
+        .into_iter()
+        .flatten()
+    {
+        instances.extend(dates(property).filter_map(|value| rdate(&value)));
+    }
+
+    let excluded = event
+        .inner
+        .multi_properties()
+        .get("EXDATE")
+        .into_iter()
+        .flatten()
+        .flat_map(dates)
+        .filter_map(|value| DatePerhapsTime::from_property(&value).and_then(to_local_time))
+        .collect::<Vec<_>>();
+    instances.retain(|instance| {
+        to_local_time(instance.start.clone()).is_some_and(|start| !excluded.contains(&start))
+    });
+
+    instances.sort_by_key(|instance| to_local_time(instance.start.clone()));
+    instances.dedup_by_key(|instance| to_local_time(instance.start.clone()));
+
+    Some(instances)
+}
+
+impl Rule {
+    fn parse(value: &str, start: &DatePerhapsTime) -> Option<Self> {
+        let mut rule = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            days: Vec::new(),
+            week_start: Weekday::Mon,
+        };
+        let mut frequency = None;
+
+        for part in value.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part.split_once('=')?;
+            match name.to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return None,
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value.parse().ok().filter(|&interval| interval > 0)?
+                }
+                "COUNT" => rule.count = Some(value.parse().ok()?),
+                "UNTIL" => rule.until = Some(until(value, start)?),
+                "WKST" => rule.week_start = weekday(value)?,
+                "BYDAY" => {
+                    rule.days = value.split(',').map(weekday).collect::<Option<_>>()?;
+                }
+                _ => return None,
+            }
+        }
+
+        rule.frequency = frequency?;
+        if !rule.days.is_empty() && !matches!(rule.frequency, Frequency::Weekly) {
+            return None;
+        }
+
+        Some(rule)
+    }
+
+    /// The candidates of the `index`th period after the first instance, in order.
+    fn candidates(&self, first: NaiveDateTime, index: u32) -> Vec<NaiveDateTime> {
+        let step = index.saturating_mul(self.interval);
+        let date = first.date();
+        let dates = match self.frequency {
+            Frequency::Daily => vec![date.checked_add_days(Days::new(step.into()))],
+            Frequency::Weekly if self.days.is_empty() => {
+                vec![date.checked_add_days(Days::new(u64::from(step) * 7))]
+            }
+            Frequency::Weekly => {
+                let since_week_start = days_since(date.weekday(), self.week_start);
+                let week = date
+                    .checked_sub_days(Days::new(since_week_start))
+                    .and_then(|week| week.checked_add_days(Days::new(u64::from(step) * 7)));
+                let mut days = self
+                    .days
+                    .iter()
+                    .map(|&day| days_since(day, self.week_start))
+                    .collect::<Vec<_>>();
+                days.sort_unstable();
+                days.dedup();
+                days.into_iter()
+                    .map(|day| week.and_then(|week| week.checked_add_days(Days::new(day))))
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let month = i64::from(date.month0()) + i64::from(step);
+                let year = i32::try_from(i64::from(date.year()) + month / 12).ok();
+                vec![year.and_then(|year| {
+                    NaiveDate::from_ymd_opt(year, (month % 12) as u32 + 1, date.day())
+                })]
+            }
+            Frequency::Yearly => {
+                let year = i32::try_from(i64::from(date.year()) + i64::from(step)).ok();
+                vec![year.and_then(|year| NaiveDate::from_ymd_opt(year, date.month(), date.day()))]
+            }
+        };
+
+        // Invalid dates, like the 31st of shorter months, are skipped
+        dates
+            .into_iter()
+            .flatten()
+            .map(|date| date.and_time(first.time()))
+            .collect()
+    }
+}
+
+fn days_since(day: Weekday, week_start: Weekday) -> u64 {
+    ((day.num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7).into()
+}
+
+fn weekday(value: &str) -> Option<Weekday> {
+    match value.to_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// The last possible start of an instance, in the time zone of the first instance unless it's
+/// given in UTC. An `UNTIL` date includes the whole day.
+fn until(value: &str, start: &DatePerhapsTime) -> Option<DateTime<Tz>> {
+    match DatePerhapsTime::from_property(&Property::new("UNTIL", value))? {
+        DatePerhapsTime::Date(date) => {
+            let end_of_day = date.and_time(NaiveTime::from_hms_opt(23, 59, 59)?);
+            to_local_time(with_naive(start, end_of_day))
+        }
+        DatePerhapsTime::DateTime(CalendarDateTime::Floating(date_time)) => {
+            to_local_time(with_naive(start, date_time))
+        }
+        until => to_local_time(until),
+    }
+}
+
+/// The values of a list property as properties of their own.
+fn dates(property: &Property) -> impl Iterator<Item = Property> + '_ {
+    property.value().split(',').map(|value| {
+        let mut single = Property::new(property.key(), value);
+        for parameter in property.params().values() {
+            single.append_parameter(parameter.clone());
+        }
+        single
+    })
+}
+
+fn rdate(property: &Property) -> Option<Instance> {
+    if property.value_type() != Some(ValueType::Period) {
+        let date = DatePerhapsTime::from_property(property)?;
+        return Some(Instance {
+            start: date,
+            end: None,
+        });
+    }
+
+    // Both sides are parsed as DATE-TIME with the time zone of the period
+    let date_time = |value: &str| {
+        let mut single = Property::new("RDATE", value);
+        for parameter in property.params().values() {
+            if parameter.key() != "VALUE" {
+                single.append_parameter(parameter.clone());
+            }
+        }
+        DatePerhapsTime::from_property(&single)
+    };
+
+    let (period_start, period_end) = property.value().split_once('/')?;
+    let period_start = date_time(period_start)?;
+    let period_end = match duration(period_end) {
+        Some(duration) => with_naive(&period_start, naive(&period_start) + duration),
+        None => date_time(period_end)?,
+    };
+
+    Some(Instance {
+        start: period_start,
+        end: Some(period_end),
+    })
+}
+
+/// An ISO 8601 duration as used by iCalendar, e.g. `PT1H30M` or `-P1W`.
+fn duration(value: &str) -> Option<TimeDelta> {
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P')?;
+
+    let mut total = TimeDelta::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() => in_time = true,
+            _ => {
+                let amount = number.parse::<i64>().ok()?;
+                number.clear();
+                total += match (c, in_time) {
+                    ('W', false) => TimeDelta::try_weeks(amount)?,
+                    ('D', false) => TimeDelta::try_days(amount)?,
+                    ('H', true) => TimeDelta::try_hours(amount)?,
+                    ('M', true) => TimeDelta::try_minutes(amount)?,
+                    ('S', true) => TimeDelta::try_seconds(amount)?,
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    number.is_empty().then_some(total * sign)
+}
+
+/// The date and time as written, in whatever time zone it is given.
+fn naive(date: &DatePerhapsTime) -> NaiveDateTime {
+    match date {
+        DatePerhapsTime::Date(date) => date.and_time(NaiveTime::MIN),
+        DatePerhapsTime::DateTime(CalendarDateTime::Utc(date_time)) => date_time.naive_utc(),
+        DatePerhapsTime::DateTime(CalendarDateTime::Floating(date_time)) => *date_time,
+        DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone { date_time, .. }) => *date_time,
+    }
+}
+
+/// Another date and time of the same kind and in the same time zone as `date`.
+fn with_naive(date: &DatePerhapsTime, date_time: NaiveDateTime) -> DatePerhapsTime {
+    match date {
+        DatePerhapsTime::Date(_) => DatePerhapsTime::Date(date_time.date()),
+        DatePerhapsTime::DateTime(CalendarDateTime::Utc(_)) => {
+            DatePerhapsTime::DateTime(CalendarDateTime::Utc(date_time.and_utc()))
+        }
+        DatePerhapsTime::DateTime(CalendarDateTime::Floating(_)) => {
+            DatePerhapsTime::DateTime(CalendarDateTime::Floating(date_time))
+        }
+        DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone { tzid, .. }) => {
+            DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone {
+                date_time,
+                tzid: tzid.clone(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, NaiveDateTime, TimeZone};
+    use chrono_tz::Tz;
+
+    use super::{duration, instances, occurrences};
+    use crate::testing::events;
+    use crate::TIMEZONE;
+
+    fn local(date: &str) -> DateTime<Tz> {
+        let date_time =
+            NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").expect("Invalid date");
+        TIMEZONE.from_local_datetime(&date_time).unwrap()
+    }
+
+    /// Start and end of the instances in `from..to`, as local `YYYY-MM-DD HH:MM`.
+    fn times(properties: &[&str], from: &str, to: &str) -> Option<Vec<(String, String)>> {
+        let event = &events(&[properties])[0];
+        let format = |date_time: Option<DateTime<Tz>>| {
+            date_time
+                .map(|date_time| date_time.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default()
+        };
+
+        let instances = instances(event, local(from), local(to))?;
+        Some(
+            instances
+                .iter()
+                .map(|instance| (format(instance.start()), format(instance.end())))
+                .collect(),
+        )
+    }
+
+    fn starts(properties: &[&str], from: &str, to: &str) -> Vec<String> {
+        times(properties, from, to)
+            .expect("Rule not expanded")
+            .into_iter()
+            .map(|(start, _)| start)
+            .collect()
+    }
+
+    const LECTURE: [&str; 2] = [
+        "DTSTART;TZID=Europe/Berlin:20251020T100000",
+        "DTEND;TZID=Europe/Berlin:20251020T120000",
+    ];
+
+    #[test]
+    fn weekly_with_count_and_exceptions() {
+        let event = [
+            LECTURE[0],
+            LECTURE[1],
+            "RRULE:FREQ=WEEKLY;COUNT=4",
+            "EXDATE;TZID=Europe/Berlin:20251027T100000",
+        ];
+        assert_eq!(
+            times(&event, "2025-10-01 00:00", "2026-04-01 00:00").unwrap(),
+            [
+                (
+                    "2025-10-20 10:00".to_string(),
+                    "2025-10-20 12:00".to_string()
+                ),
+                (
+                    "2025-11-03 10:00".to_string(),
+                    "2025-11-03 12:00".to_string()
+                ),
+                (
+                    "2025-11-10 10:00".to_string(),
+                    "2025-11-10 12:00".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_local_time_across_dst() {
+        let event = [LECTURE[0], LECTURE[1], "RRULE:FREQ=WEEKLY"];
+        assert_eq!(
+            starts(&event, "2025-10-22 00:00", "2025-11-04 00:00"),
+            ["2025-10-27 10:00", "2025-11-03 10:00"]
+        );
+
+        let utc = ["DTSTART:20251020T080000Z", "RRULE:FREQ=WEEKLY;COUNT=2"];
+        assert_eq!(
+            starts(&utc, "2025-10-01 00:00", "2025-11-01 00:00"),
+            ["2025-10-20 10:00", "2025-10-27 09:00"]
+        );
+    }
+
+    #[test]
+    fn weekly_on_days_with_interval() {
+        let event = [
+            LECTURE[0],
+            LECTURE[1],
+            "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=WE,MO;UNTIL=20251105",
+        ];
+        assert_eq!(
+            starts(&event, "2025-10-01 00:00", "2026-04-01 00:00"),
+            [
+                "2025-10-20 10:00",
+                "2025-10-22 10:00",
+                "2025-11-03 10:00",
+                "2025-11-05 10:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn daily_until_date_time() {
+        let event = [
+            LECTURE[0],
+            "RRULE:FREQ=DAILY;INTERVAL=3;UNTIL=20251026T090000Z",
+        ];
+        assert_eq!(
+            starts(&event, "2025-10-01 00:00", "2026-04-01 00:00"),
+            ["2025-10-20 10:00", "2025-10-23 10:00", "2025-10-26 10:00"]
+        );
+    }
+
+    #[test]
+    fn monthly_and_yearly_skip_invalid_dates() {
+        let monthly = [
+            "DTSTART;TZID=Europe/Berlin:20250131T090000",
+            "RRULE:FREQ=MONTHLY;COUNT=3",
+        ];
+        assert_eq!(
+            starts(&monthly, "2025-01-01 00:00", "2026-01-01 00:00"),
+            ["2025-01-31 09:00", "2025-03-31 09:00", "2025-05-31 09:00"]
+        );
+
+        let yearly = ["DTSTART;VALUE=DATE:20240229", "RRULE:FREQ=YEARLY"];
+        assert_eq!(
+            starts(&yearly, "2024-01-01 00:00", "2033-01-01 00:00"),
+            ["2024-02-29 00:00", "2028-02-29 00:00", "2032-02-29 00:00"]
+        );
+    }
+
+    #[test]
+    fn only_instances_in_range() {
+        let event = [LECTURE[0], LECTURE[1], "RRULE:FREQ=WEEKLY"];
+        assert_eq!(
+            starts(&event, "2025-11-10 11:00", "2025-11-24 10:00"),
+            ["2025-11-10 10:00", "2025-11-17 10:00"]
+        );
+    }
+
+    #[test]
+    fn additional_dates_and_periods() {
+        let event = [
+            LECTURE[0],
+            LECTURE[1],
+            "RDATE;TZID=Europe/Berlin:20251024T140000,20251031T140000",
+            "RDATE;VALUE=PERIOD:20251107T130000Z/PT3H,20251114T130000Z/20251114T140000Z",
+        ];
+        assert_eq!(
+            times(&event, "2025-10-01 00:00", "2026-04-01 00:00").unwrap(),
+            [
+                (
+                    "2025-10-20 10:00".to_string(),
+                    "2025-10-20 12:00".to_string()
+                ),
+                ("2025-10-24 14:00".to_string(), String::new()),
+                ("2025-10-31 14:00".to_string(), String::new()),
+                (
+                    "2025-11-07 14:00".to_string(),
+                    "2025-11-07 17:00".to_string()
+                ),
+                (
+                    "2025-11-14 14:00".to_string(),
+                    "2025-11-14 15:00".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn instances_are_plain_events() {
+        let event = &events(&[&[
+            LECTURE[0],
+            LECTURE[1],
+            "RRULE:FREQ=WEEKLY;COUNT=2",
+            "EXDATE;TZID=Europe/Berlin:20251020T100000",
+        ]])[0];
+        let instances = instances(event, local("2025-10-01 00:00"), local("2026-01-01 00:00"))
+            .expect("Rule not expanded");
+
+        assert_eq!(instances.len(), 1);
+        assert!(!instances[0].is_recurring());
+        assert_eq!(instances[0].summary, event.summary);
+    }
+
+    #[test]
+    fn unsupported_rules_are_not_expanded() {
+        let event = [LECTURE[0], LECTURE[1], "RRULE:FREQ=MONTHLY;BYMONTHDAY=-1"];
+        assert_eq!(times(&event, "2025-10-01 00:00", "2026-04-01 00:00"), None);
+
+        let event = &events(&[&event])[0];
+        let shown = occurrences(event, local("2025-10-01 00:00"), local("2026-04-01 00:00"));
+        assert_eq!(shown.len(), 1);
+        assert!(shown[0].is_recurring());
+    }
+
+    #[test]
+    fn durations() {
+        let cases = [
+            ("PT2H", Some(2 * 3600)),
+            ("PT1H30M", Some(5400)),
+            ("-PT15M", Some(-900)),
+            ("+P1D", Some(86400)),
+            ("P1W", Some(604800)),
+            ("P1DT12H", Some(129600)),
+            ("P", Some(0)),
+            ("PT", Some(0)),
+            ("P1H", None),
+            ("PT1D", None),
+            ("PT1", None),
+            ("20251020T100000Z", None),
+        ];
+        for (input, seconds) in cases {
+            assert_eq!(
+                duration(input).map(|duration| duration.num_seconds()),
+                seconds,
+                "{}",
+                input
+            );
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use chrono::{Datelike, NaiveDate};
+
+/// A semester at TUM, the winter semester running from October to March and the summer
+/// semester from April to September.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Semester {
+    /// First day of the semester.
+    pub start: NaiveDate,
+    /// Last day of the semester.
+    pub end: NaiveDate,
+}
+
+impl Semester {
+    /// The semester the date falls into.
+    pub fn containing(date: NaiveDate) -> Self {
+        let year = date.year();
+        let (start, end) = match date.month() {
+            1..=3 => (ymd(year - 1, 10, 1), ymd(year, 3, 31)),
+            4..=9 => (ymd(year, 4, 1), ymd(year, 9, 30)),
+            _ => (ymd(year, 10, 1), ymd(year + 1, 3, 31)),
+        };
+
+        Self { start, end }
+    }
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("Semester boundaries are valid dates")
+}
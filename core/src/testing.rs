@@ -0,0 +1,32 @@
+//! Helpers shared by the unit tests.
+
+use ical::IcalParser;
+
+use crate::event::Event;
+use crate::pipeline::Pipeline;
+use crate::transform;
+
+/// Events with the given properties, passed through the transformation without any stages.
+///
+/// Each event gets a UID and, unless given, a summary.
+pub(crate) fn events(events: &[&[&str]]) -> Vec<Event> {
+    let mut lines = vec!["BEGIN:VCALENDAR".to_string(), "VERSION:2.0".to_string()];
+    for (i, properties) in events.iter().enumerate() {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:event-{}", i));
+        if !properties.iter().any(|line| line.starts_with("SUMMARY")) {
+            lines.push("SUMMARY:Test".to_string());
+        }
+        lines.extend(properties.iter().map(|line| line.to_string()));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.extend(["END:VCALENDAR".to_string(), String::new()]);
+
+    let parsed = IcalParser::new(lines.join("\r\n").as_bytes())
+        .next()
+        .expect("No calendar")
+        .expect("Invalid calendar");
+    let calendar = transform(parsed, &Pipeline::new()).expect("Transform failed");
+
+    calendar.events().to_vec()
+}
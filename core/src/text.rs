@@ -0,0 +1,72 @@
+//! Escaping of TEXT values, see RFC 5545, section 3.3.11.
+
+/// Properties whose value is a comma-separated list of TEXT values.
+///
+/// Their values are kept escaped, since unescaping would merge the list into a single value.
+pub const LIST_PROPERTIES: &[&str] = &["CATEGORIES", "RESOURCES"];
+
+/// Turns an escaped TEXT value into the text it represents.
+///
+/// Backslashes that do not start a valid escape sequence are kept as they are, since some
+/// producers do not escape them.
+pub fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n' | 'N') => result.push('\n'),
+            Some(c @ ('\\' | ';' | ',')) => result.push(c),
+            Some(c) => {
+                result.push('\\');
+                result.push(c);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+/// Splits an escaped list of TEXT values at the unescaped commas, unescaping each value.
+pub fn split_list(value: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                if let Some(c) = chars.next() {
+                    current.push(c);
+                }
+            }
+            ',' => values.push(unescape(&std::mem::take(&mut current))),
+            c => current.push(c),
+        }
+    }
+    values.push(unescape(&current));
+
+    values
+}
+
+/// Escapes text so it can be used as a TEXT value.
+pub fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            ';' => result.push_str("\\;"),
+            ',' => result.push_str("\\,"),
+            '\n' => result.push_str("\\n"),
+            '\r' => {}
+            c => result.push(c),
+        }
+    }
+
+    result
+}
@@ -0,0 +1,247 @@
+use ical::parser::ical::component::IcalCalendar;
+use icalendar::{Calendar as iCalendar, Component, Property};
+
+use crate::event::Event;
+use crate::pipeline::Pipeline;
+use crate::utils::{
+    from_alarm, from_event, from_free_busy, from_journal, from_property, from_timezone, from_todo,
+};
+use crate::{Calendar, TransformError};
+
+/// Cleans up a calendar as exported by CAMPUSonline.
+///
+/// Events are run through the stages of the pipeline, all other components are passed
+/// through as they are.
+pub fn transform(calendar: IcalCalendar, pipeline: &Pipeline) -> Result<Calendar, TransformError> {
+    let mut result = iCalendar::new();
+    {
+        let mut prod_id_prop = result
+            .properties
+            .iter_mut()
+            .filter(|property| property.key() == "PRODID");
+
+        if let Some(prop) = prod_id_prop.next() {
+            *prop = Property::new("PRODID", "TUM-CalProxy/0.1");
+        } else {
+            result.append_property(Property::new("PRODID", "TUM-CalProxy/0.1"));
+        }
+    }
+
+    for property in calendar.properties {
+        if property.name.as_str() == "PRODID" {
+            continue;
+        }
+        let Some(property) = from_property(property) else {
+            continue;
+        };
+        // `iCalendar::new` already sets these, and they may only occur once
+        let existing = result
+            .properties
+            .iter_mut()
+            .filter(|existing| matches!(existing.key(), "VERSION" | "CALSCALE"))
+            .find(|existing| existing.key() == property.key());
+        match existing {
+            Some(existing) => *existing = property,
+            None => {
+                result.append_property(property);
+            }
+        }
+    }
+
+    // Only events are transformed, everything else is passed through as-is
+    for timezone in calendar.timezones {
+        result.push(from_timezone(timezone));
+    }
+    for todo in calendar.todos {
+        result.push(from_todo(todo));
+    }
+    for journal in calendar.journals {
+        result.push(from_journal(journal));
+    }
+    for free_busy in calendar.free_busys {
+        result.push(from_free_busy(free_busy));
+    }
+    for alarm in calendar.alarms {
+        result.push(from_alarm(alarm));
+    }
+
+    let mut events = Vec::new();
+    for event in calendar.events {
+        let event = from_event(event)?;
+        // Events without a summary carry no information worth keeping
+        let Some(summary) = event.get_summary().map(|summary| summary.to_string()) else {
+            continue;
+        };
+        events.push(Event::new(event, summary));
+    }
+    pipeline.run(&mut events);
+
+    Ok(Calendar::new(result, events))
+}
+
+#[cfg(test)]
+mod tests {
+    use ical::parser::ical::component::IcalCalendar;
+    use ical::property::Property;
+    use ical::IcalParser;
+
+    use super::transform;
+    use crate::pipeline::Pipeline;
+
+    const CALENDAR: &str = r"BEGIN:VCALENDAR
+VERSION:2.0
+PRODID:-//CAMPUSonline//DE
+CALSCALE:GREGORIAN
+X-WR-CALNAME:Mein Kalender\, TUM
+BEGIN:VTIMEZONE
+TZID:Europe/Berlin
+BEGIN:STANDARD
+DTSTART:19701025T030000
+TZOFFSETFROM:+0200
+TZOFFSETTO:+0100
+TZNAME:CET
+RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU
+END:STANDARD
+BEGIN:DAYLIGHT
+DTSTART:19700329T020000
+TZOFFSETFROM:+0100
+TZOFFSETTO:+0200
+TZNAME:CEST
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU
+END:DAYLIGHT
+END:VTIMEZONE
+BEGIN:VEVENT
+UID:event-1@tum.de
+DTSTAMP:20251001T000000Z
+DTSTART;TZID=Europe/Berlin:20251020T100000
+DTEND;TZID=Europe/Berlin:20251020T120000
+SUMMARY:Einführung in die Informatik (IN0001) VO\, Standardgruppe
+DESCRIPTION:Erste Zeile\nZweite Zeile\; mit Semikolon
+LOCATION:Hörsaal 1 (5602.EG.001)
+RRULE:FREQ=WEEKLY;COUNT=14
+EXDATE;TZID=Europe/Berlin:20251027T100000
+EXDATE;TZID=Europe/Berlin:20251103T100000
+ATTENDEE;CN=Erika Mustermann;ROLE=REQ-PARTICIPANT:mailto:erika@tum.de
+ATTENDEE;CN=Max Mustermann;ROLE=OPT-PARTICIPANT:mailto:max@tum.de
+BEGIN:VALARM
+ACTION:DISPLAY
+DESCRIPTION:Erinnerung
+TRIGGER:-PT15M
+END:VALARM
+END:VEVENT
+BEGIN:VTODO
+UID:todo-1@tum.de
+DTSTAMP:20251001T000000Z
+DUE;VALUE=DATE:20251031
+SUMMARY:Übungsblatt abgeben
+STATUS:NEEDS-ACTION
+END:VTODO
+BEGIN:VJOURNAL
+UID:journal-1@tum.de
+DTSTAMP:20251001T000000Z
+DTSTART;VALUE=DATE:20251020
+SUMMARY:Notizen
+DESCRIPTION:Komma\, Semikolon\; Backslash\\
+END:VJOURNAL
+END:VCALENDAR
+";
+
+    fn parse(ics: &str) -> IcalCalendar {
+        IcalParser::new(ics.as_bytes())
+            .next()
+            .expect("No calendar")
+            .expect("Invalid calendar")
+    }
+
+    /// Properties as sorted `NAME;PARAM=VALUE:value` lines, so that order doesn't matter.
+    fn lines(properties: &[Property]) -> Vec<String> {
+        let mut lines = properties
+            .iter()
+            .filter(|property| property.name != "PRODID")
+            .map(|property| {
+                let mut line = property.name.clone();
+                let mut params = property.params.clone().unwrap_or_default();
+                params.sort();
+                for (name, values) in params {
+                    line.push_str(&format!(";{}={}", name, values.join(",")));
+                }
+                line.push(':');
+                line.push_str(property.value.as_deref().unwrap_or_default());
+                line
+            })
+            .collect::<Vec<_>>();
+        lines.sort();
+        lines
+    }
+
+    fn round_trip() -> (IcalCalendar, IcalCalendar) {
+        let calendar = transform(parse(CALENDAR), &Pipeline::new()).expect("Transform failed");
+        (parse(CALENDAR), parse(&calendar.to_ics()))
+    }
+
+    #[test]
+    fn keeps_calendar_properties() {
+        let (input, output) = round_trip();
+        assert_eq!(lines(&output.properties), lines(&input.properties));
+    }
+
+    #[test]
+    fn keeps_timezone_transitions() {
+        let (input, output) = round_trip();
+        assert_eq!(output.timezones.len(), 1);
+        let (input, output) = (&input.timezones[0], &output.timezones[0]);
+        assert_eq!(lines(&output.properties), lines(&input.properties));
+
+        assert_eq!(output.transitions.len(), 2);
+        for (input, output) in input.transitions.iter().zip(&output.transitions) {
+            assert_eq!(
+                format!("{:?}", output.transition),
+                format!("{:?}", input.transition)
+            );
+            assert_eq!(lines(&output.properties), lines(&input.properties));
+        }
+    }
+
+    #[test]
+    fn keeps_events_with_alarms() {
+        let (input, output) = round_trip();
+        assert_eq!(output.events.len(), 1);
+        let (input, output) = (&input.events[0], &output.events[0]);
+        assert_eq!(lines(&output.properties), lines(&input.properties));
+
+        assert_eq!(output.alarms.len(), 1);
+        assert_eq!(
+            lines(&output.alarms[0].properties),
+            lines(&input.alarms[0].properties)
+        );
+    }
+
+    #[test]
+    fn keeps_multi_valued_properties() {
+        let (_, output) = round_trip();
+        let count = |name: &str| {
+            output.events[0]
+                .properties
+                .iter()
+                .filter(|property| property.name == name)
+                .count()
+        };
+        assert_eq!(count("EXDATE"), 2);
+        assert_eq!(count("ATTENDEE"), 2);
+    }
+
+    #[test]
+    fn keeps_todos_and_journals() {
+        let (input, output) = round_trip();
+        assert_eq!(output.todos.len(), 1);
+        assert_eq!(
+            lines(&output.todos[0].properties),
+            lines(&input.todos[0].properties)
+        );
+        assert_eq!(output.journals.len(), 1);
+        assert_eq!(
+            lines(&output.journals[0].properties),
+            lines(&input.journals[0].properties)
+        );
+    }
+}
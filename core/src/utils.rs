@@ -0,0 +1,138 @@
+use chrono::{DateTime, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+use ical::parser::ical::component::{
+    IcalAlarm, IcalEvent, IcalFreeBusy, IcalJournal, IcalTimeZone, IcalTimeZoneTransitionType,
+    IcalTodo,
+};
+use icalendar::parser::{Component, Property};
+use icalendar::{CalendarComponent, CalendarDateTime, DatePerhapsTime, ValueType};
+
+use crate::text;
+use crate::{TransformError, TIMEZONE};
+
+/// Converts a property, unescaping TEXT values.
+///
+/// The `ical` parser keeps values escaped, while `icalendar` expects the actual text and
+/// escapes it again when writing the calendar.
+pub fn from_property(property: ical::property::Property) -> Option<icalendar::Property> {
+    let mut prop = if let Some(value) = property.value {
+        icalendar::Property::new(property.name, value)
+    } else {
+        return None;
+    };
+
+    if let Some(parameters) = property.params {
+        for (name, value) in parameters {
+            prop.append_parameter(icalendar::Parameter::new(
+                name.as_str(),
+                value.join(",").as_str(),
+            ));
+        }
+    }
+
+    if prop.value_type() == Some(ValueType::Text) && !text::LIST_PROPERTIES.contains(&prop.key()) {
+        let mut unescaped = icalendar::Property::new(prop.key(), text::unescape(prop.value()));
+        for parameter in prop.params().values() {
+            unescaped.append_parameter(parameter.clone());
+        }
+        prop = unescaped;
+    }
+
+    Some(prop)
+}
+
+// Components are converted via the parser types of `icalendar`, as that is the only way to
+// create components of arbitrary kinds (e.g. `VTIMEZONE`) and it takes care of properties that
+// may occur more than once.
+
+fn parsed_property(property: ical::property::Property) -> Option<Property<'static>> {
+    from_property(property).map(Property::from)
+}
+
+fn parsed_component(
+    kind: &'static str,
+    properties: Vec<ical::property::Property>,
+    components: Vec<Component<'static>>,
+) -> Component<'static> {
+    Component {
+        name: kind.into(),
+        properties: properties.into_iter().filter_map(parsed_property).collect(),
+        components,
+    }
+}
+
+fn parsed_alarm(alarm: IcalAlarm) -> Component<'static> {
+    parsed_component("VALARM", alarm.properties, Vec::new())
+}
+
+pub fn from_event(event: IcalEvent) -> Result<icalendar::Event, TransformError> {
+    let alarms = event.alarms.into_iter().map(parsed_alarm).collect();
+    match parsed_component("VEVENT", event.properties, alarms).into() {
+        CalendarComponent::Event(event) => Ok(event),
+        _ => Err(TransformError::InvalidComponent("VEVENT".to_string())),
+    }
+}
+
+pub fn from_todo(todo: IcalTodo) -> CalendarComponent {
+    let alarms = todo.alarms.into_iter().map(parsed_alarm).collect();
+    parsed_component("VTODO", todo.properties, alarms).into()
+}
+
+pub fn from_alarm(alarm: IcalAlarm) -> CalendarComponent {
+    parsed_alarm(alarm).into()
+}
+
+pub fn from_journal(journal: IcalJournal) -> CalendarComponent {
+    parsed_component("VJOURNAL", journal.properties, Vec::new()).into()
+}
+
+pub fn from_free_busy(free_busy: IcalFreeBusy) -> CalendarComponent {
+    parsed_component("VFREEBUSY", free_busy.properties, Vec::new()).into()
+}
+
+pub fn from_timezone(timezone: IcalTimeZone) -> CalendarComponent {
+    let transitions = timezone
+        .transitions
+        .into_iter()
+        .map(|transition| {
+            let kind = match transition.transition {
+                IcalTimeZoneTransitionType::STANDARD => "STANDARD",
+                IcalTimeZoneTransitionType::DAYLIGHT => "DAYLIGHT",
+            };
+            parsed_component(kind, transition.properties, Vec::new())
+        })
+        .collect();
+
+    parsed_component("VTIMEZONE", timezone.properties, transitions).into()
+}
+
+data_macro::building_id_matcher!(
+    pub fn match_building_id("data/buildings.json")
+);
+
+data_macro::course_name_replacer!(
+    pub fn replace_course_name("data/courses.json")
+);
+
+/// Converts a date or date-time to local time, see [`TIMEZONE`].
+///
+/// Dates start at midnight, floating times and unknown time zones are taken as local time.
+pub fn to_local_time(date: DatePerhapsTime) -> Option<DateTime<Tz>> {
+    match date {
+        DatePerhapsTime::Date(date) => TIMEZONE
+            .from_local_datetime(&date.and_time(NaiveTime::MIN))
+            .earliest(),
+        DatePerhapsTime::DateTime(CalendarDateTime::Utc(date_time)) => {
+            Some(date_time.with_timezone(&TIMEZONE))
+        }
+        DatePerhapsTime::DateTime(CalendarDateTime::Floating(date_time)) => {
+            TIMEZONE.from_local_datetime(&date_time).earliest()
+        }
+        DatePerhapsTime::DateTime(CalendarDateTime::WithTimezone { date_time, tzid }) => {
+            let tz = tzid.parse::<Tz>().unwrap_or(TIMEZONE);
+            tz.from_local_datetime(&date_time)
+                .earliest()
+                .map(|date_time| date_time.with_timezone(&TIMEZONE))
+        }
+    }
+}
@@ -22,7 +22,13 @@ impl Parse for JsonFileInput {
         let path = content.parse::<syn::LitStr>()?;
         let span = path.span();
 
-        let Ok(path) = path.value().parse::<PathBuf>();
+        let Ok(mut path) = path.value().parse::<PathBuf>();
+        // Relative to the crate using the macro, not to wherever the compiler is run
+        if path.is_relative() {
+            if let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
+                path = PathBuf::from(manifest_dir).join(path);
+            }
+        }
 
         let file = match File::open(&path) {
             Ok(file) => file,
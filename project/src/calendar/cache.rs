@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ical::parser::ical::component::IcalCalendar;
+use sha2::{Digest, Sha256};
+
+use crate::calendar::source::{FetchedCalendar, Id};
+
+/// Key of a cached subscription.
+///
+/// The key is a SHA-256 hash over the upstream URL, the student/person number and the token,
+/// so that neither of them is ever kept in memory longer than the request itself.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CacheKey([u8; 32]);
+
+struct CacheEntry {
+    fetched: FetchedCalendar,
+    fetched_at: Instant,
+}
+
+/// TTL- and size-bounded cache of upstream calendars, shared between all workers.
+///
+/// Entries are kept past their TTL for up to `max_staleness`, so that the last known-good
+/// calendar can still be served while TUMOnline is unavailable.
+#[derive(Clone)]
+pub struct CalendarCache {
+    entries: Arc<Mutex<HashMap<CacheKey, CacheEntry>>>,
+    ttl: Duration,
+    max_entries: usize,
+    max_staleness: Duration,
+    stale_note: bool,
+}
+
+/// A calendar served from the cache after the upstream fetch failed.
+pub struct StaleCalendar {
+    pub calendar: IcalCalendar,
+    pub age: Duration,
+}
+
+impl CacheKey {
+    pub fn new(base_url: &str, id: &Id, token: &str) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(base_url.as_bytes());
+        hasher.update(b"?");
+        match id {
+            Id::Student(student_number) => {
+                hasher.update(b"pStud=");
+                hasher.update(student_number.as_bytes());
+            }
+            Id::Person(person_number) => {
+                hasher.update(b"pPers=");
+                hasher.update(person_number.as_bytes());
+            }
+        }
+        hasher.update(b"&pToken=");
+        hasher.update(token.as_bytes());
+
+        Self(hasher.finalize().into())
+    }
+}
+
+impl CalendarCache {
+    pub fn new(
+        ttl: Duration,
+        max_entries: usize,
+        max_staleness: Duration,
+        stale_note: bool,
+    ) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+            max_entries,
+            max_staleness,
+            stale_note,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.max_entries > 0 && !self.retention().is_zero()
+    }
+
+    /// How long entries are kept at all, either to be served fresh or stale.
+    fn retention(&self) -> Duration {
+        self.ttl.max(self.max_staleness)
+    }
+
+    /// Whether stale calendars should carry a note about their age in `X-WR-CALDESC`.
+    pub fn stale_note(&self) -> bool {
+        self.stale_note
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<IcalCalendar> {
+        self.get_at(key, Instant::now())
+    }
+
+    fn get_at(&self, key: &CacheKey, now: Instant) -> Option<IcalCalendar> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let mut entries = self.entries.lock().expect("Calendar cache lock poisoned");
+        match entries.get(key) {
+            Some(entry) if entry.age(now) < self.ttl => Some(entry.fetched.calendar.clone()),
+            Some(entry) if entry.age(now) >= self.retention() => {
+                entries.remove(key);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the last known-good calendar, as long as it is not older than the maximum
+    /// staleness.
+    pub fn get_stale(&self, key: &CacheKey) -> Option<StaleCalendar> {
+        self.get_stale_at(key, Instant::now())
+    }
+
+    fn get_stale_at(&self, key: &CacheKey, now: Instant) -> Option<StaleCalendar> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let entries = self.entries.lock().expect("Calendar cache lock poisoned");
+        entries
+            .get(key)
+            .filter(|entry| entry.age(now) < self.max_staleness)
+            .map(|entry| StaleCalendar {
+                calendar: entry.fetched.calendar.clone(),
+                age: entry.age(now),
+            })
+    }
+
+    /// Returns the retained calendar together with its validators, so that it can be
+    /// revalidated with TUMOnline instead of being downloaded again.
+    pub fn get_revalidation(&self, key: &CacheKey) -> Option<FetchedCalendar> {
+        self.get_revalidation_at(key, Instant::now())
+    }
+
+    fn get_revalidation_at(&self, key: &CacheKey, now: Instant) -> Option<FetchedCalendar> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let entries = self.entries.lock().expect("Calendar cache lock poisoned");
+        entries
+            .get(key)
+            .filter(|entry| entry.age(now) < self.retention())
+            .filter(|entry| {
+                entry.fetched.validators.etag.is_some()
+                    || entry.fetched.validators.last_modified.is_some()
+            })
+            .map(|entry| entry.fetched.clone())
+    }
+
+    pub fn insert(&self, key: CacheKey, fetched: FetchedCalendar) {
+        self.insert_at(key, fetched, Instant::now());
+    }
+
+    fn insert_at(&self, key: CacheKey, fetched: FetchedCalendar, now: Instant) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let mut entries = self.entries.lock().expect("Calendar cache lock poisoned");
+        if !entries.contains_key(&key) && entries.len() >= self.max_entries {
+            let retention = self.retention();
+            entries.retain(|_, entry| entry.age(now) < retention);
+
+            if entries.len() >= self.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.fetched_at)
+                    .map(|(key, _)| *key);
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+
+        entries.insert(
+            key,
+            CacheEntry {
+                fetched,
+                fetched_at: now,
+            },
+        );
+    }
+}
+
+impl CacheEntry {
+    fn age(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.fetched_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use ical::parser::ical::component::IcalCalendar;
+
+    use super::{CacheKey, CalendarCache};
+    use crate::calendar::source::{FetchedCalendar, Id, Validators};
+
+    const TTL: Duration = Duration::from_secs(60);
+    const MAX_STALENESS: Duration = Duration::from_secs(600);
+
+    fn key(number: &str) -> CacheKey {
+        CacheKey::new(
+            "https://campus.tum.de/tumonline",
+            &Id::Student(number.to_string()),
+            "x",
+        )
+    }
+
+    fn fetched(etag: Option<&str>) -> FetchedCalendar {
+        FetchedCalendar {
+            calendar: IcalCalendar::new(),
+            validators: Validators {
+                etag: etag.map(str::to_string),
+                last_modified: None,
+            },
+        }
+    }
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn keys_depend_on_all_parts() {
+        let base = "https://campus.tum.de/tumonline";
+        let student = Id::Student("ge12abc".to_string());
+        let person = Id::Person("ge12abc".to_string());
+
+        assert_eq!(
+            CacheKey::new(base, &student, "x"),
+            CacheKey::new(base, &student, "x")
+        );
+        assert_ne!(
+            CacheKey::new(base, &student, "x"),
+            CacheKey::new(base, &person, "x")
+        );
+        assert_ne!(
+            CacheKey::new(base, &student, "x"),
+            CacheKey::new(base, &student, "y")
+        );
+        assert_ne!(
+            CacheKey::new(base, &student, "x"),
+            CacheKey::new("https://other", &student, "x")
+        );
+    }
+
+    #[test]
+    fn serves_entries_within_their_ttl() {
+        let cache = CalendarCache::new(TTL, 10, MAX_STALENESS, false);
+        let now = Instant::now();
+        cache.insert_at(key("a"), fetched(None), now);
+
+        assert!(cache.get_at(&key("a"), now).is_some());
+        assert!(cache.get_at(&key("a"), now + TTL - secs(1)).is_some());
+        assert!(cache.get_at(&key("a"), now + TTL).is_none());
+        assert!(cache.get_at(&key("b"), now).is_none());
+    }
+
+    #[test]
+    fn retains_stale_entries() {
+        let cache = CalendarCache::new(TTL, 10, MAX_STALENESS, false);
+        let now = Instant::now();
+        cache.insert_at(key("a"), fetched(Some("\"v1\"")), now);
+
+        let stale = cache.get_stale_at(&key("a"), now + secs(120));
+        assert_eq!(stale.map(|stale| stale.age), Some(secs(120)));
+        let revalidation = cache.get_revalidation_at(&key("a"), now + secs(120));
+        assert_eq!(
+            revalidation.and_then(|fetched| fetched.validators.etag),
+            Some("\"v1\"".to_string())
+        );
+
+        assert!(cache.get_stale_at(&key("a"), now + MAX_STALENESS).is_none());
+        assert!(cache
+            .get_revalidation_at(&key("a"), now + MAX_STALENESS)
+            .is_none());
+
+        // Looking up an entry past its retention removes it
+        assert!(cache.get_at(&key("a"), now + MAX_STALENESS).is_none());
+        assert!(cache.entries.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn revalidates_only_entries_with_validators() {
+        let cache = CalendarCache::new(TTL, 10, MAX_STALENESS, false);
+        let now = Instant::now();
+        cache.insert_at(key("a"), fetched(None), now);
+
+        assert!(cache
+            .get_revalidation_at(&key("a"), now + secs(120))
+            .is_none());
+        assert!(cache.get_stale_at(&key("a"), now + secs(120)).is_some());
+    }
+
+    #[test]
+    fn evicts_the_oldest_entry() {
+        let cache = CalendarCache::new(TTL, 2, MAX_STALENESS, false);
+        let now = Instant::now();
+        cache.insert_at(key("b"), fetched(None), now + secs(1));
+        cache.insert_at(key("a"), fetched(None), now);
+        cache.insert_at(key("c"), fetched(None), now + secs(2));
+
+        let later = now + secs(3);
+        assert!(cache.get_at(&key("a"), later).is_none());
+        assert!(cache.get_at(&key("b"), later).is_some());
+        assert!(cache.get_at(&key("c"), later).is_some());
+
+        // Replacing an entry doesn't evict another one
+        cache.insert_at(key("b"), fetched(None), later);
+        assert!(cache.get_at(&key("c"), later).is_some());
+        assert_eq!(cache.entries.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn drops_expired_entries_before_evicting() {
+        let cache = CalendarCache::new(TTL, 3, Duration::ZERO, false);
+        let now = Instant::now();
+        cache.insert_at(key("a"), fetched(None), now);
+        cache.insert_at(key("b"), fetched(None), now + secs(1));
+        cache.insert_at(key("c"), fetched(None), now + secs(30));
+        cache.insert_at(key("d"), fetched(None), now + TTL + secs(1));
+
+        // Both expired entries are dropped, not just the oldest one
+        let later = now + TTL + secs(2);
+        assert_eq!(cache.entries.lock().unwrap().len(), 2);
+        assert!(cache.get_at(&key("c"), later).is_some());
+        assert!(cache.get_at(&key("d"), later).is_some());
+    }
+
+    #[test]
+    fn disabled_caches_keep_nothing() {
+        let now = Instant::now();
+        for cache in [
+            CalendarCache::new(TTL, 0, MAX_STALENESS, false),
+            CalendarCache::new(Duration::ZERO, 10, Duration::ZERO, false),
+        ] {
+            cache.insert_at(key("a"), fetched(Some("\"v1\"")), now);
+            assert!(cache.get_at(&key("a"), now).is_none());
+            assert!(cache.get_stale_at(&key("a"), now).is_none());
+            assert!(cache.get_revalidation_at(&key("a"), now).is_none());
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use tracing::info;
+
+use crate::calendar::cache::CacheKey;
+use crate::calendar::source::{FetchError, FetchedCalendar};
+
+type SharedFetch = Shared<BoxFuture<'static, Result<FetchedCalendar, FetchError>>>;
+
+/// Coalesces concurrent upstream fetches for the same subscription.
+///
+/// The first request for a subscription starts the fetch, every request arriving while it is
+/// still in flight waits for the same fetch and receives the same result or error.
+#[derive(Clone, Default)]
+pub struct FetchCoalescer {
+    in_flight: Arc<Mutex<HashMap<CacheKey, SharedFetch>>>,
+}
+
+impl FetchCoalescer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn fetch<F>(&self, key: CacheKey, fetch: F) -> Result<FetchedCalendar, FetchError>
+    where
+        F: Future<Output = Result<FetchedCalendar, FetchError>> + Send + 'static,
+    {
+        let shared = {
+            let mut in_flight = self.in_flight.lock().expect("In-flight map lock poisoned");
+            if let Some(shared) = in_flight.get(&key) {
+                info!("Joining in-flight fetch for the same calendar");
+                shared.clone()
+            } else {
+                let shared = fetch.boxed().shared();
+                in_flight.insert(key, shared.clone());
+                shared
+            }
+        };
+
+        let mut waiting = Waiting {
+            coalescer: self,
+            key,
+            shared: shared.clone(),
+            done: false,
+        };
+        let result = shared.await;
+        waiting.done = true;
+
+        result
+    }
+}
+
+/// A request waiting for an in-flight fetch.
+///
+/// Removes the fetch once it is done, or once the last request waiting for it is cancelled, so
+/// that abandoned fetches don't stay around.
+struct Waiting<'a> {
+    coalescer: &'a FetchCoalescer,
+    key: CacheKey,
+    /// Handle that is never polled, only used to identify the fetch.
+    shared: SharedFetch,
+    done: bool,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self
+            .coalescer
+            .in_flight
+            .lock()
+            .expect("In-flight map lock poisoned");
+        if !in_flight
+            .get(&self.key)
+            .is_some_and(|current| current.ptr_eq(&self.shared))
+        {
+            return;
+        }
+
+        // Handles of the map, this request and the future it awaited, if not dropped yet, while
+        // every other waiting request holds two more
+        let others_waiting = self.shared.strong_count().is_some_and(|count| count > 3);
+        if self.done || !others_waiting {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::task::Poll;
+
+    use futures_util::future::poll_fn;
+    use futures_util::{join, poll};
+    use ical::parser::ical::component::IcalCalendar;
+
+    use super::FetchCoalescer;
+    use crate::calendar::cache::CacheKey;
+    use crate::calendar::source::{FetchError, FetchedCalendar, Id, Validators};
+
+    fn key(number: &str) -> CacheKey {
+        CacheKey::new(
+            "https://campus.tum.de/tumonline",
+            &Id::Student(number.to_string()),
+            "x",
+        )
+    }
+
+    /// A fetch that is still in flight when first polled, counting how often it's started.
+    fn fetch(
+        started: &Arc<AtomicUsize>,
+        result: Result<&str, FetchError>,
+    ) -> impl std::future::Future<Output = Result<FetchedCalendar, FetchError>> + Send + 'static
+    {
+        slow_fetch(started, result, 1)
+    }
+
+    /// A fetch that is pending for the given number of polls.
+    fn slow_fetch(
+        started: &Arc<AtomicUsize>,
+        result: Result<&str, FetchError>,
+        mut pending: usize,
+    ) -> impl std::future::Future<Output = Result<FetchedCalendar, FetchError>> + Send + 'static
+    {
+        let started = started.clone();
+        let result = result.map(str::to_string);
+        async move {
+            started.fetch_add(1, Ordering::SeqCst);
+            poll_fn(|cx| {
+                if pending > 0 {
+                    pending -= 1;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                } else {
+                    Poll::Ready(())
+                }
+            })
+            .await;
+
+            result.map(|etag| FetchedCalendar {
+                calendar: IcalCalendar::new(),
+                validators: Validators {
+                    etag: Some(etag),
+                    last_modified: None,
+                },
+            })
+        }
+    }
+
+    fn etag(result: Result<FetchedCalendar, FetchError>) -> Option<String> {
+        result.ok().and_then(|fetched| fetched.validators.etag)
+    }
+
+    fn in_flight(coalescer: &FetchCoalescer) -> usize {
+        coalescer.in_flight.lock().unwrap().len()
+    }
+
+    #[actix_web::test]
+    async fn joins_in_flight_fetches() {
+        let coalescer = FetchCoalescer::new();
+        let started = Arc::new(AtomicUsize::new(0));
+
+        let (first, second) = join!(
+            coalescer.fetch(key("a"), fetch(&started, Ok("\"first\""))),
+            coalescer.fetch(key("a"), fetch(&started, Ok("\"second\""))),
+        );
+
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+        assert_eq!(etag(first), Some("\"first\"".to_string()));
+        assert_eq!(etag(second), Some("\"first\"".to_string()));
+        assert_eq!(in_flight(&coalescer), 0);
+    }
+
+    #[actix_web::test]
+    async fn shares_errors() {
+        let coalescer = FetchCoalescer::new();
+        let started = Arc::new(AtomicUsize::new(0));
+
+        let (first, second) = join!(
+            coalescer.fetch(key("a"), fetch(&started, Err(FetchError::Upstream))),
+            coalescer.fetch(key("a"), fetch(&started, Ok("\"second\""))),
+        );
+
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+        assert!(matches!(first, Err(FetchError::Upstream)));
+        assert!(matches!(second, Err(FetchError::Upstream)));
+        assert_eq!(in_flight(&coalescer), 0);
+    }
+
+    #[actix_web::test]
+    async fn fetches_other_keys_and_later_requests_separately() {
+        let coalescer = FetchCoalescer::new();
+        let started = Arc::new(AtomicUsize::new(0));
+
+        let (first, second) = join!(
+            coalescer.fetch(key("a"), fetch(&started, Ok("\"a\""))),
+            coalescer.fetch(key("b"), fetch(&started, Ok("\"b\""))),
+        );
+        assert_eq!(started.load(Ordering::SeqCst), 2);
+        assert_eq!(etag(first), Some("\"a\"".to_string()));
+        assert_eq!(etag(second), Some("\"b\"".to_string()));
+
+        // The finished fetch is not reused
+        let third = coalescer
+            .fetch(key("a"), fetch(&started, Ok("\"a2\"")))
+            .await;
+        assert_eq!(started.load(Ordering::SeqCst), 3);
+        assert_eq!(etag(third), Some("\"a2\"".to_string()));
+        assert_eq!(in_flight(&coalescer), 0);
+    }
+
+    #[actix_web::test]
+    async fn cleans_up_after_cancelled_requests() {
+        let coalescer = FetchCoalescer::new();
+        let started = Arc::new(AtomicUsize::new(0));
+
+        let first_fetch = slow_fetch(&started, Ok("\"first\""), 3);
+        let mut first = Box::pin(coalescer.fetch(key("a"), first_fetch));
+        let mut second = Box::pin(coalescer.fetch(key("a"), fetch(&started, Ok("\"second\""))));
+        assert!(poll!(first.as_mut()).is_pending());
+        assert!(poll!(second.as_mut()).is_pending());
+
+        // The fetch is kept for the request still waiting for it
+        drop(first);
+        assert_eq!(in_flight(&coalescer), 1);
+        assert_eq!(etag(second.await), Some("\"first\"".to_string()));
+        assert_eq!(in_flight(&coalescer), 0);
+
+        // An abandoned fetch is removed, so the next request starts a new one
+        let mut third = Box::pin(coalescer.fetch(key("a"), fetch(&started, Ok("\"third\""))));
+        assert!(poll!(third.as_mut()).is_pending());
+        drop(third);
+        assert_eq!(in_flight(&coalescer), 0);
+
+        let fourth = coalescer
+            .fetch(key("a"), fetch(&started, Ok("\"fourth\"")))
+            .await;
+        assert_eq!(started.load(Ordering::SeqCst), 3);
+        assert_eq!(etag(fourth), Some("\"fourth\"".to_string()));
+        assert_eq!(in_flight(&coalescer), 0);
+    }
+}
@@ -0,0 +1,130 @@
+use actix_web::http::header::{self, Header};
+use actix_web::HttpRequest;
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+/// Output format of a calendar, selected via `format` or the `Accept` header.
+///
+/// Plain text, Markdown and org-mode are only available via `format`, as clients accept
+/// `text/plain` for all kinds of responses.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Ics,
+    Json,
+    Jcal,
+    Xcal,
+    Csv,
+    Text,
+    Markdown,
+    Org,
+    /// One iCalendar file per course and one with all events.
+    Zip,
+    Freebusy,
+}
+
+/// The part of the calendar shown by formats that don't include all events.
+pub struct FormatOptions {
+    /// Any day of the week shown by agenda formats.
+    pub week: NaiveDate,
+    /// First day of the free/busy range.
+    pub from: NaiveDate,
+    /// Last day of the free/busy range.
+    pub to: NaiveDate,
+}
+
+impl Format {
+    /// Uses the requested format, falling back to the most preferred one in `Accept` and
+    /// iCalendar if none of them is supported.
+    pub fn negotiate(requested: Option<Format>, req: &HttpRequest) -> Self {
+        if let Some(format) = requested {
+            return format;
+        }
+
+        header::Accept::parse(req)
+            .ok()
+            .and_then(|accept| {
+                accept
+                    .ranked()
+                    .iter()
+                    .find_map(|mime| Self::from_media_type(mime.essence_str()))
+            })
+            .unwrap_or(Format::Ics)
+    }
+
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type {
+            "text/calendar" => Some(Format::Ics),
+            "application/json" => Some(Format::Json),
+            "application/calendar+json" => Some(Format::Jcal),
+            "application/calendar+xml" => Some(Format::Xcal),
+            "text/csv" => Some(Format::Csv),
+            "application/zip" => Some(Format::Zip),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Format::Ics | Format::Freebusy => "text/calendar;charset=utf-8",
+            Format::Json => "application/json",
+            Format::Jcal => "application/calendar+json",
+            Format::Xcal => "application/calendar+xml;charset=utf-8",
+            Format::Csv => "text/csv;charset=utf-8;header=present",
+            Format::Text => "text/plain;charset=utf-8",
+            Format::Markdown => "text/markdown;charset=utf-8",
+            Format::Org => "text/org;charset=utf-8",
+            Format::Zip => "application/zip",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Ics | Format::Freebusy => "ics",
+            Format::Json | Format::Jcal => "json",
+            Format::Xcal => "xcs",
+            Format::Csv => "csv",
+            Format::Text => "txt",
+            Format::Markdown => "md",
+            Format::Org => "org",
+            Format::Zip => "zip",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header;
+    use actix_web::test::TestRequest;
+
+    use super::Format;
+
+    fn negotiate(requested: Option<Format>, accept: &str) -> Format {
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, accept))
+            .to_http_request();
+        Format::negotiate(requested, &req)
+    }
+
+    #[test]
+    fn negotiates_calendar_formats() {
+        assert_eq!(negotiate(None, "text/calendar"), Format::Ics);
+        assert_eq!(negotiate(None, "application/calendar+json"), Format::Jcal);
+        assert_eq!(
+            negotiate(None, "text/html;q=0.9, application/calendar+xml"),
+            Format::Xcal
+        );
+        assert_eq!(negotiate(None, "*/*"), Format::Ics);
+        assert_eq!(negotiate(Some(Format::Csv), "text/calendar"), Format::Csv);
+    }
+
+    #[test]
+    fn text_formats_need_to_be_requested() {
+        assert_eq!(negotiate(None, "text/plain"), Format::Ics);
+        assert_eq!(negotiate(None, "text/markdown, text/plain"), Format::Ics);
+        assert_eq!(negotiate(Some(Format::Text), "text/calendar"), Format::Text);
+        assert_eq!(negotiate(Some(Format::Markdown), "*/*"), Format::Markdown);
+        assert_eq!(negotiate(None, "text/org"), Format::Ics);
+        assert_eq!(negotiate(Some(Format::Org), "text/calendar"), Format::Org);
+    }
+}
@@ -1,30 +1,49 @@
 use std::collections::HashSet;
-use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
 
 use actix_web::http::header;
-use actix_web::{Error, HttpResponse};
-use icalendar::{Calendar as iCalendar, Component, EventLike, Property};
-use lazy_regex::regex;
-use regex::Regex;
-use reqwest::Client;
-use tracing::info;
-
-use crate::calendar::event_type::{EventType, Filter};
-use crate::calendar::fetch::{fetch_calendar, Id};
-use crate::calendar::utils::{from_event, from_property};
+use actix_web::{Error, HttpRequest, HttpResponse};
+use chrono::NaiveDate;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::{error, info, warn};
+use tum_cal_core::event_type::{EventType, Filter, ScopedEventType};
+use tum_cal_core::{transform, IgnoreList, Options, Pipeline, StageKind};
+
+use crate::calendar::cache::{CacheKey, CalendarCache};
+use crate::calendar::coalesce::FetchCoalescer;
+use crate::calendar::format::{Format, FormatOptions};
+use crate::calendar::source::{CalendarSource, FetchError, FetchResponse, Id, SourceRequest};
+use crate::calendar::tenant::Tenants;
+use crate::error::{InternalServerError, InvalidQuery, UnknownTenant};
 use crate::handlers::cal::QueryArgs;
+use crate::utils::today;
 
-pub mod event_type;
-mod fetch;
-mod utils;
+pub mod cache;
+pub mod coalesce;
+pub mod format;
+pub mod source;
+pub mod tenant;
 
 pub struct Calendar {
-    inner: iCalendar,
+    inner: tum_cal_core::Calendar,
+    /// Age of the upstream calendar if it was served stale because TUMOnline failed.
+    stale: Option<Duration>,
 }
 
 impl Calendar {
-    pub async fn from_query(query: QueryArgs, client: Client) -> Result<Self, Error> {
+    pub async fn from_query(
+        query: QueryArgs,
+        source: Arc<dyn CalendarSource>,
+        cache: CalendarCache,
+        coalescer: FetchCoalescer,
+        tenants: Tenants,
+    ) -> Result<Self, Error> {
         let id = Id::from_student_or_person_number(query.student_number, query.person_number)?;
+        let tenant = tenants
+            .get(query.tenant.as_deref())
+            .ok_or_else(|| UnknownTenant::new(query.tenant.clone().unwrap_or_default()))?;
 
         match &id {
             Id::Student(id) => {
@@ -35,196 +54,461 @@ impl Calendar {
             }
         }
 
-        let calendar = fetch_calendar(client, id, query.token).await?;
-
-        let filter = if let Some(include) = query.include {
-            Filter::new_include(include.iter().copied().collect::<HashSet<_>>())
-        } else if let Some(exclude) = query.exclude {
-            Filter::new_exclude(exclude.iter().copied().collect::<HashSet<_>>())
+        let cache_key = CacheKey::new(tenant.url(), &id, &query.token);
+        let mut stale = None;
+        let calendar = if let Some(calendar) = cache.get(&cache_key) {
+            info!("Calendar cache hit");
+            calendar
         } else {
-            Filter::new_none()
+            info!("Calendar cache miss, fetching from source");
+            let cached = cache.get_revalidation(&cache_key);
+            let request = SourceRequest {
+                base_url: tenant.url().to_string(),
+                id,
+                token: query.token,
+                validators: cached.as_ref().map(|cached| cached.validators.clone()),
+            };
+            let response = source.fetch(request);
+            let fetch = async move {
+                match response.await? {
+                    FetchResponse::Modified(fetched) => Ok(*fetched),
+                    FetchResponse::NotModified => {
+                        info!("Calendar not modified since the last fetch");
+                        cached.ok_or(FetchError::Upstream)
+                    }
+                }
+            };
+            match coalescer.fetch(cache_key, fetch).await {
+                Ok(fetched) => {
+                    cache.insert(cache_key, fetched.clone());
+                    fetched.calendar
+                }
+                Err(FetchError::Upstream) => {
+                    let Some(cached) = cache.get_stale(&cache_key) else {
+                        return Err(FetchError::Upstream.into());
+                    };
+                    warn!(
+                        age = cached.age.as_secs(),
+                        "Serving stale calendar because the source failed"
+                    );
+                    stale = Some(cached.age);
+                    cached.calendar
+                }
+                Err(e) => return Err(e.into()),
+            }
         };
 
-        let ignored_events = query
-            .ignore
-            .unwrap_or_default()
-            .into_iter()
-            .collect::<HashSet<_>>();
+        let (filter, course_filters) = type_filters(
+            query.include.unwrap_or_default(),
+            query.exclude.unwrap_or_default(),
+        );
 
-        let mut result = iCalendar::new();
-        {
-            let mut prod_id_prop = result
-                .properties
-                .iter_mut()
-                .filter(|property| property.key() == "PRODID");
+        let ignored_events = IgnoreList::parse(query.ignore.unwrap_or_default())
+            .map_err(|e| InvalidQuery::new(e.to_string()))?;
 
-            if let Some(prop) = prod_id_prop.next() {
-                *prop = Property::new("PRODID", "TUM-CalProxy/0.1");
+        let options = Options {
+            filter,
+            course_filters,
+            ignore: ignored_events,
+            course: query.course,
+            groups: query.groups.unwrap_or_default(),
+            from: query
+                .from
+                .map(|from| from.resolve_start(today()))
+                .transpose()
+                .map_err(|e| InvalidQuery::new(e.to_string()))?,
+            to: query
+                .to
+                .map(|to| to.resolve_end(today()))
+                .transpose()
+                .map_err(|e| InvalidQuery::new(e.to_string()))?,
+            weekdays: query
+                .weekdays
+                .map(|weekdays| weekdays.iter().map(|day| day.0).collect()),
+            after: query.after,
+            before: query.before,
+            data: tenant.data(),
+        };
+        let stages = query.stages.as_deref().unwrap_or(&StageKind::DEFAULT);
+        let pipeline = Pipeline::from_kinds(stages, options);
+        let mut result = transform(calendar, &pipeline).map_err(|e| {
+            error!("Error transforming calendar: {}", e);
+            InternalServerError::new()
+        })?;
+
+        if let Some(age) = stale.filter(|_| cache.stale_note()) {
+            result.add_note(&format!(
+                "TUMOnline ist derzeit nicht erreichbar, dieser Kalender ist {} Minuten alt.",
+                age.as_secs() / 60
+            ));
+        }
+
+        Ok(Self {
+            inner: result,
+            stale,
+        })
+    }
+
+    pub fn to_response(
+        &self,
+        req: &HttpRequest,
+        format: Format,
+        options: &FormatOptions,
+    ) -> HttpResponse {
+        let body = match format {
+            Format::Ics => self.inner.to_ics().into_bytes(),
+            Format::Json => self.inner.to_json().into_bytes(),
+            Format::Jcal => self.inner.to_jcal().into_bytes(),
+            Format::Xcal => self.inner.to_xcal().into_bytes(),
+            Format::Csv => self.inner.to_csv().into_bytes(),
+            Format::Text => self.inner.to_text(options.week).into_bytes(),
+            Format::Markdown => self.inner.to_markdown(options.week).into_bytes(),
+            Format::Org => self.inner.to_org().into_bytes(),
+            Format::Zip => self.inner.to_zip(),
+            Format::Freebusy => self
+                .inner
+                .to_freebusy(options.from, options.to)
+                .into_bytes(),
+        };
+        let filename = format!("calendar.{}", format.extension());
+
+        self.respond(req, body, format.content_type(), Some(&filename))
+    }
+
+    /// Renders the week containing `week` as HTML agenda, linking to the other weeks with the
+    /// same query parameters.
+    pub fn to_agenda_response(&self, req: &HttpRequest, week: NaiveDate) -> HttpResponse {
+        let query = query_without(req, &["week"]);
+        let link = |week: NaiveDate| {
+            if query.is_empty() {
+                format!("?week={}", week)
             } else {
-                result.append_property(Property::new("PRODID", "TUM-CalProxy/0.1"));
+                format!("?{}&week={}", query, week)
             }
+        };
+        let body = self.inner.to_agenda(week, link);
+
+        self.respond(req, body, "text/html;charset=utf-8", None)
+    }
+
+    /// Lists the courses of the calendar as JSON, each with the URL of its own feed.
+    pub fn to_courses_response(&self, req: &HttpRequest) -> HttpResponse {
+        let connection = req.connection_info();
+        // The feed is the parent of this endpoint, wherever the proxy is mounted
+        let path = req.path().strip_suffix("/courses").unwrap_or(req.path());
+        let query = query_without(req, &["course", "format", "week"]);
+        let courses = self
+            .inner
+            .courses()
+            .into_iter()
+            .map(|course| CourseListing {
+                url: format!(
+                    "{}://{}{}?{}&course={}",
+                    connection.scheme(),
+                    connection.host(),
+                    path,
+                    query,
+                    form_urlencoded::byte_serialize(course.key.as_bytes()).collect::<String>()
+                ),
+                key: course.key,
+                name: course.full_name,
+                short_name: course.short_name,
+                ids: course.ids,
+                types: course.types,
+                events: course.events,
+            })
+            .collect::<Vec<_>>();
+        let body = serde_json::to_string(&courses).expect("Could not serialize courses");
+
+        self.respond(req, body, "application/json", None)
+    }
+
+    /// Renders the week containing `week` as SVG timetable.
+    pub fn to_timetable_response(&self, req: &HttpRequest, week: NaiveDate) -> HttpResponse {
+        let body = self.inner.to_timetable(week);
+
+        self.respond(req, body, "image/svg+xml", None)
+    }
+
+    fn respond(
+        &self,
+        req: &HttpRequest,
+        body: impl Into<Vec<u8>>,
+        content_type: &str,
+        filename: Option<&str>,
+    ) -> HttpResponse {
+        let body = body.into();
+        let etag = format!("\"{:x}\"", Sha256::digest(&body));
+
+        let not_modified = matches_etag(req, &etag);
+        let mut response = if not_modified {
+            HttpResponse::NotModified()
+        } else {
+            HttpResponse::Ok()
+        };
+        response
+            .append_header((header::ETAG, etag))
+            .append_header((header::VARY, "Accept"));
+        if let Some(age) = self.stale {
+            response
+                .append_header((header::WARNING, "110 - \"Response is Stale\""))
+                .append_header((header::AGE, age.as_secs()));
         }
 
-        for property in calendar.properties {
-            if property.name.as_str() == "PRODID" {
-                continue;
-            }
-            if let Some(property) = from_property(property) {
-                result.append_property(property);
-            }
+        if not_modified {
+            return response.finish();
         }
 
-        let mut already_parsed = HashSet::new();
-        for event in calendar.events {
-            let mut event = from_event(event);
+        if let Some(filename) = filename {
+            response.append_header((
+                header::CONTENT_DISPOSITION,
+                format!("attachment;filename={}", filename),
+            ));
+        }
+        response
+            .append_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+            .append_header((header::CONTENT_TYPE, content_type))
+            .append_header((header::CONTENT_LANGUAGE, "de"))
+            .body(body)
+    }
+}
 
-            let summary = if let Some(summary) = event.get_summary() {
-                summary.replace("\\", "")
-            } else {
-                continue;
-            };
+/// A course in the course list, see [`Calendar::to_courses_response`].
+#[derive(Serialize)]
+struct CourseListing {
+    key: String,
+    name: String,
+    short_name: String,
+    ids: Vec<String>,
+    types: Vec<EventType>,
+    events: usize,
+    /// Feed with only the events of this course.
+    url: String,
+}
 
-            let dedup_key = format!("{}-{:?}", summary, event.get_start(),);
-            if already_parsed.contains(&dedup_key) {
-                continue;
-            } else {
-                already_parsed.insert(dedup_key);
+/// Splits include and exclude entries into the global filter and per-course filters.
+///
+/// Like the global filter, a course filter includes types if any are included for the course
+/// and excludes them otherwise.
+fn type_filters(
+    include: Vec<ScopedEventType>,
+    exclude: Vec<ScopedEventType>,
+) -> (Filter, Vec<(String, Filter)>) {
+    let filter = |course: Option<&str>| {
+        let types = |entries: &[ScopedEventType]| {
+            entries
+                .iter()
+                .filter(|entry| entry.course.as_deref() == course)
+                .map(|entry| entry.typ)
+                .collect::<HashSet<_>>()
+        };
+        let (include, exclude) = (types(&include), types(&exclude));
+        if !include.is_empty() {
+            Filter::new_include(include)
+        } else if !exclude.is_empty() {
+            Filter::new_exclude(exclude)
+        } else {
+            Filter::new_none()
+        }
+    };
+
+    let mut courses: Vec<&str> = Vec::new();
+    for entry in include.iter().chain(&exclude) {
+        if let Some(course) = entry.course.as_deref() {
+            if !courses.contains(&course) {
+                courses.push(course);
             }
+        }
+    }
+    let course_filters = courses
+        .iter()
+        .map(|course| (course.to_string(), filter(Some(course))))
+        .collect();
 
-            let name_reg: &Regex = regex!(
-                r#"(?x)
-                (?<name> .*? )
-                \s?
-                (?: [ \(\[ ]
-                    (?<id> [A-Z]{2,3}[0-9]+ (?:, \s? [A-Z]{2,3}[0-9]+)* )
-                [ \)\] ] \s? )?
-                (?<tag> [A-Z]{2} ),
-                \s?
-                (?<group> .* )
-            "#
-            );
-            let captures = match name_reg.captures(&summary) {
-                Some(captures) => captures,
-                None => {
-                    // Add the event anyway, but without any additional information or filtering
-                    info!("Encountered event with unknown format: {}", summary);
-                    result.push(event);
-                    continue;
-                }
-            };
+    (filter(None), course_filters)
+}
 
-            let mut full_name = captures["name"].trim().to_string();
-            if full_name.ends_with(" -") {
-                full_name.pop();
-                full_name.pop();
-            }
-            if let Some(desc) = event.get_description() {
-                // For some reason this is necessary for some courses
-                if desc.contains("Zentralübung") {
-                    full_name.push_str(" - Zentralübung");
-                }
-            }
+/// The query string of the request without the given parameters.
+fn query_without(req: &HttpRequest, names: &[&str]) -> String {
+    req.query_string()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| {
+            let name = pair.split_once('=').map_or(*pair, |(name, _)| name);
+            !names.contains(&name)
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
 
-            let ids = captures
-                .name("id")
-                .map(|id| {
-                    id.as_str()
-                        .split(",")
-                        .map(|id| id.trim())
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or_default();
-            let typ = if let Ok(id) = captures["tag"].parse::<EventType>() {
-                id
-            } else {
-                info!("Encountered unknown event type: {}", &captures["tag"]);
-                result.push(event);
-                continue;
-            };
-            let group = captures["group"].trim();
+/// Checks whether the client already has the current version, see RFC 9110, section 13.1.2.
+fn matches_etag(req: &HttpRequest, etag: &str) -> bool {
+    let Some(if_none_match) = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
 
-            if !filter.contains(typ) {
-                continue;
-            }
-            if ignored_events.contains(&full_name) {
-                continue;
-            }
-            if ids.iter().any(|&id| ignored_events.contains(id)) {
-                continue;
-            }
+    if_none_match
+        .split(',')
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
 
-            let name = utils::replace_course_name(full_name.clone());
-            event.summary(name.as_str());
-
-            let room_reg: &Regex = regex!(
-                r#"(?x)
-                \(
-                (?<building_id> \d{4} ) \.
-                (?<floor> \d\d|EG|UG|DG|Z\d|U\d ) \.
-                (?<room_id> [\dA-Z]+ )
-                \)
-            "#
-            );
-
-            let mut room = None;
-            if let Some(loc) = event.get_location() {
-                let loc = loc.replace("\\", "");
-
-                if let Some(captures) = room_reg.captures(&loc) {
-                    let building_id = &captures["building_id"];
-                    let building_id: u16 = match building_id.parse() {
-                        Ok(id) => id,
-                        Err(_) => {
-                            info!("Encountered invalid building ID: {}", building_id);
-                            continue;
-                        }
-                    };
-                    if let Some(address) = utils::match_building_id(building_id) {
-                        room = Some(loc);
-                        event.location(address);
-                    } else {
-                        info!("Encountered unknown building ID: {}", building_id)
-                    }
-                } else if !loc.starts_with("Online") {
-                    info!("Encountered location with unknown format: {}", loc);
-                }
-            }
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
 
-            let mut description = String::new();
-            writeln!(&mut description, "Name: {}", full_name).expect("Could not write to string");
-            writeln!(&mut description, "Typ: {} ({})", typ, typ.id())
-                .expect("Could not write to string");
-            if !ids.is_empty() {
-                writeln!(&mut description, "IDs: {}", ids.join(", "))
-                    .expect("Could not write to string");
-            }
-            if let Some(room) = room {
-                writeln!(&mut description, "Raum: {}", room).expect("Could not write to string");
-            }
-            writeln!(&mut description, "Gruppe: {}", group).expect("Could not write to string");
-            writeln!(&mut description, "\n------------\n\n").expect("Could not write to string");
-            write!(&mut description, "{}", summary).expect("Could not write to string");
-            if let Some(desc) = event.get_description() {
-                let desc = desc.replace("\\", "");
-                write!(&mut description, "\n\n{}", desc).expect("Could not write to string");
-            }
-            event.description(description.as_str());
+    use actix_web::http::StatusCode;
+    use actix_web::web::Query;
+    use actix_web::Error;
+    use ical::IcalParser;
+    use icalendar::Component;
 
-            result.push(event);
+    use super::Calendar;
+    use crate::calendar::cache::CalendarCache;
+    use crate::calendar::coalesce::FetchCoalescer;
+    use crate::calendar::source::{CalendarSource, MemorySource};
+    use crate::calendar::tenant::Tenants;
+    use crate::handlers::cal::QueryArgs;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR
+VERSION:2.0
+PRODID:-//CAMPUSonline//DE
+BEGIN:VEVENT
+UID:lecture
+DTSTAMP:20251001T000000Z
+DTSTART;TZID=Europe/Berlin:20251020T100000
+DTEND;TZID=Europe/Berlin:20251020T120000
+SUMMARY:Einführung in die Informatik (IN0001) VO, Standardgruppe
+END:VEVENT
+BEGIN:VEVENT
+UID:exercise
+DTSTAMP:20251001T000000Z
+DTSTART;TZID=Europe/Berlin:20251028T140000
+DTEND;TZID=Europe/Berlin:20251028T160000
+SUMMARY:Einführung in die Informatik (IN0001) UE, Gruppe 03
+END:VEVENT
+BEGIN:VEVENT
+UID:analysis
+DTSTAMP:20251001T000000Z
+DTSTART;TZID=Europe/Berlin:20251022T080000
+DTEND;TZID=Europe/Berlin:20251022T100000
+SUMMARY:Analysis für Informatik (MA0902) VO, Standardgruppe
+END:VEVENT
+END:VCALENDAR
+";
+
+    fn source(numbers: &[&str]) -> Arc<dyn CalendarSource> {
+        let mut source = MemorySource::new();
+        for number in numbers {
+            let calendar = IcalParser::new(CALENDAR.replace('\n', "\r\n").as_bytes())
+                .next()
+                .expect("No calendar")
+                .expect("Invalid calendar");
+            source.insert(number.to_string(), calendar);
         }
 
-        Ok(Self { inner: result })
+        Arc::new(source)
     }
 
-    pub fn to_response(&self) -> HttpResponse {
-        HttpResponse::Ok()
-            .append_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
-            .append_header((header::CONTENT_TYPE, "text/calendar;charset=utf-8"))
-            .append_header((
-                header::CONTENT_DISPOSITION,
-                "attachment;filename=calendar.ics",
-            ))
-            .append_header((header::CONTENT_LANGUAGE, "de"))
-            .body(self.inner.to_string())
+    fn cache() -> CalendarCache {
+        CalendarCache::new(Duration::from_secs(60), 10, Duration::from_secs(600), false)
+    }
+
+    async fn from_query(
+        query: &str,
+        source: Arc<dyn CalendarSource>,
+        cache: CalendarCache,
+    ) -> Result<Calendar, Error> {
+        let query = Query::<QueryArgs>::from_query(query)
+            .expect("Invalid query")
+            .into_inner();
+        let tenants = Tenants::load("https://campus.tum.de/tumonline".to_string(), None);
+
+        Calendar::from_query(query, source, cache, FetchCoalescer::new(), tenants).await
+    }
+
+    async fn uids(query: &str) -> Vec<String> {
+        let calendar = from_query(query, source(&["ge12abc"]), cache())
+            .await
+            .expect("Query failed");
+
+        calendar
+            .inner
+            .events()
+            .iter()
+            .filter_map(|event| event.inner.get_uid())
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn status(result: Result<Calendar, Error>) -> StatusCode {
+        match result {
+            Ok(_) => StatusCode::OK,
+            Err(e) => e.as_response_error().status_code(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn transforms_the_fetched_calendar() {
+        let mut all = uids("pStud=ge12abc&pToken=x").await;
+        all.sort();
+        assert_eq!(all, ["analysis", "exercise", "lecture"]);
+
+        assert_eq!(
+            uids("pStud=ge12abc&pToken=x&include=VO").await,
+            ["lecture", "analysis"]
+        );
+        assert_eq!(
+            uids("pStud=ge12abc&pToken=x&exclude=IN0001:VO").await,
+            ["exercise", "analysis"]
+        );
+        assert_eq!(
+            uids("pStud=ge12abc&pToken=x&course=MA0902").await,
+            ["analysis"]
+        );
+        assert_eq!(
+            uids("pStud=ge12abc&pToken=x&from=2025-10-21&to=2025-10-27").await,
+            ["analysis"]
+        );
+    }
+
+    #[actix_web::test]
+    async fn serves_cached_calendars() {
+        let cache = cache();
+        let query = "pPers=ge12abc&pToken=x";
+        assert!(from_query(query, source(&["ge12abc"]), cache.clone())
+            .await
+            .is_ok());
+
+        // The source no longer knows the number, but the cache does
+        let calendar = from_query(query, source(&[]), cache.clone()).await;
+        assert_eq!(
+            calendar.map(|calendar| calendar.inner.events().len()).ok(),
+            Some(3)
+        );
+
+        // Other tokens are different subscriptions
+        let other = from_query("pPers=ge12abc&pToken=y", source(&[]), cache).await;
+        assert_eq!(status(other), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn rejects_invalid_queries() {
+        let source = || source(&["ge12abc"]);
+        let cases = [
+            "pToken=x",
+            "pStud=unknown&pToken=x",
+            "pStud=ge12abc&pToken=x&tenant=unknown",
+            "pStud=ge12abc&pToken=x&ignore=re:(",
+        ];
+        for query in cases {
+            let result = from_query(query, source(), cache()).await;
+            assert_eq!(status(result), StatusCode::BAD_REQUEST, "{}", query);
+        }
     }
 }
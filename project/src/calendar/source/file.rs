@@ -0,0 +1,147 @@
+use std::path::PathBuf;
+
+use actix_web::web;
+use futures_util::future::{self, BoxFuture};
+use futures_util::FutureExt;
+use tracing::{error, info};
+
+use crate::calendar::source::{
+    parse_calendar, CalendarSource, FetchError, FetchResponse, SourceRequest, Validators,
+};
+
+/// Reads calendars from `<directory>/<student or person number>.ics` on every request.
+///
+/// Numbers other than letters and digits are rejected, so that they can't point outside of
+/// the directory.
+pub struct FileSource {
+    directory: PathBuf,
+}
+
+impl FileSource {
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+}
+
+impl CalendarSource for FileSource {
+    fn fetch(
+        &self,
+        request: SourceRequest,
+    ) -> BoxFuture<'static, Result<FetchResponse, FetchError>> {
+        let number = request.id.number();
+        if number.is_empty() || !number.chars().all(|c| c.is_ascii_alphanumeric()) {
+            info!("Rejected calendar file name {:?}", number);
+            return future::ready(Err(FetchError::InvalidCredentials)).boxed();
+        }
+        let path = self.directory.join(format!("{}.ics", number));
+
+        async move {
+            let read_path = path.clone();
+            let bytes = match web::block(move || std::fs::read(read_path)).await {
+                Ok(Ok(bytes)) => bytes,
+                Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                    info!("No calendar file at {}", path.display());
+                    return Err(FetchError::InvalidCredentials);
+                }
+                Ok(Err(e)) => {
+                    error!("Error reading calendar file {}: {}", path.display(), e);
+                    return Err(FetchError::Upstream);
+                }
+                Err(e) => {
+                    error!("Could not read calendar file {}: {}", path.display(), e);
+                    return Err(FetchError::Upstream);
+                }
+            };
+
+            let calendar = parse_calendar(&bytes)?;
+            Ok(FetchResponse::modified(calendar, Validators::default()))
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::FileSource;
+    use crate::calendar::source::{CalendarSource, FetchError, FetchResponse, Id, SourceRequest};
+
+    /// A directory with `ge12abc.ics` and a calendar outside of it, removed when dropped.
+    struct Directory(PathBuf);
+
+    impl Directory {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!(
+                "tum-cal-file-source-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let directory = root.join("calendars");
+            std::fs::create_dir_all(&directory).expect("Could not create directory");
+
+            let calendar = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nEND:VCALENDAR\r\n";
+            std::fs::write(directory.join("ge12abc.ics"), calendar).expect("Could not write");
+            std::fs::write(root.join("x.ics"), calendar).expect("Could not write");
+
+            Self(root)
+        }
+
+        fn source(&self) -> FileSource {
+            FileSource::new(self.0.join("calendars"))
+        }
+    }
+
+    impl Drop for Directory {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    async fn fetch(source: &FileSource, id: Id) -> Result<FetchResponse, FetchError> {
+        source
+            .fetch(SourceRequest {
+                base_url: String::new(),
+                id,
+                token: String::new(),
+                validators: None,
+            })
+            .await
+    }
+
+    #[actix_web::test]
+    async fn reads_calendar_files() {
+        let directory = Directory::new("read");
+        let source = directory.source();
+
+        let result = fetch(&source, Id::Student("ge12abc".to_string())).await;
+        assert!(matches!(result, Ok(FetchResponse::Modified(_))));
+
+        let result = fetch(&source, Id::Person("ov12abc".to_string())).await;
+        assert!(matches!(result, Err(FetchError::InvalidCredentials)));
+    }
+
+    #[actix_web::test]
+    async fn rejects_numbers_outside_of_the_directory() {
+        let directory = Directory::new("reject");
+        let source = directory.source();
+
+        let numbers = [
+            "",
+            "../x",
+            "..",
+            "/tmp/x",
+            "calendars/../x",
+            "ge12abc.ics",
+            "ge 12",
+        ];
+        for number in numbers {
+            let result = fetch(&source, Id::Student(number.to_string())).await;
+            assert!(
+                matches!(result, Err(FetchError::InvalidCredentials)),
+                "{:?}",
+                number
+            );
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use futures_util::future::{self, BoxFuture};
+use futures_util::FutureExt;
+use ical::parser::ical::component::IcalCalendar;
+
+use crate::calendar::source::{
+    parse_calendar, CalendarSource, FetchError, FetchResponse, SourceRequest, Validators,
+};
+
+/// Serves a fixed set of calendars keyed by student or person number.
+#[derive(Default)]
+pub struct MemorySource {
+    calendars: HashMap<String, IcalCalendar>,
+}
+
+impl MemorySource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, number: String, calendar: IcalCalendar) {
+        self.calendars.insert(number, calendar);
+    }
+
+    /// Loads every `<student or person number>.ics` file in `directory` once.
+    ///
+    /// Panics if the directory or one of the files cannot be read.
+    pub fn load_directory(directory: &Path) -> Self {
+        let mut source = Self::new();
+
+        let entries = std::fs::read_dir(directory).unwrap_or_else(|e| {
+            panic!(
+                "Could not read calendar directory {}: {}",
+                directory.display(),
+                e
+            )
+        });
+        for entry in entries {
+            let path = entry.expect("Could not read directory entry").path();
+            if path.extension().is_none_or(|ext| ext != "ics") {
+                continue;
+            }
+
+            let bytes = std::fs::read(&path)
+                .unwrap_or_else(|e| panic!("Could not read {}: {}", path.display(), e));
+            let calendar = parse_calendar(&bytes)
+                .unwrap_or_else(|e| panic!("Could not parse {}: {}", path.display(), e));
+            let number = path
+                .file_stem()
+                .expect("Calendar file should have a name")
+                .to_string_lossy()
+                .to_string();
+            source.insert(number, calendar);
+        }
+
+        source
+    }
+}
+
+impl CalendarSource for MemorySource {
+    fn fetch(
+        &self,
+        request: SourceRequest,
+    ) -> BoxFuture<'static, Result<FetchResponse, FetchError>> {
+        let result = match self.calendars.get(request.id.number()) {
+            Some(calendar) => Ok(FetchResponse::modified(
+                calendar.clone(),
+                Validators::default(),
+            )),
+            None => Err(FetchError::InvalidCredentials),
+        };
+
+        future::ready(result).boxed()
+    }
+}
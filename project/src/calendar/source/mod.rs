@@ -0,0 +1,143 @@
+use actix_web::Error;
+use futures_util::future::BoxFuture;
+use ical::parser::ical::component::IcalCalendar;
+use ical::IcalParser;
+use std::fmt;
+use tracing::{error, warn};
+
+use crate::error::{CalendarError, InternalServerError, QueryError};
+
+mod file;
+mod memory;
+mod tumonline;
+
+pub use file::FileSource;
+pub use memory::MemorySource;
+pub use tumonline::TumOnlineSource;
+
+/// Somewhere calendars can be retrieved from.
+///
+/// The returned future must not borrow from the source, so that it can be shared between
+/// coalesced requests.
+pub trait CalendarSource: Send + Sync {
+    fn fetch(
+        &self,
+        request: SourceRequest,
+    ) -> BoxFuture<'static, Result<FetchResponse, FetchError>>;
+}
+
+pub struct SourceRequest {
+    /// Upstream URL of the tenant the calendar is requested for.
+    pub base_url: String,
+    pub id: Id,
+    pub token: String,
+    /// Validators of a previously fetched version of the calendar, if any.
+    pub validators: Option<Validators>,
+}
+
+pub enum Id {
+    Student(String),
+    Person(String),
+}
+
+/// Reasons why a calendar could not be retrieved from its source.
+#[derive(Clone, Debug)]
+pub enum FetchError {
+    /// The source rejected the student/person number or token.
+    InvalidCredentials,
+    /// The source could not be reached or returned something unusable.
+    Upstream,
+}
+
+/// Validators sent along with a calendar, used to revalidate it later on.
+#[derive(Clone, Debug, Default)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FetchedCalendar {
+    pub calendar: IcalCalendar,
+    pub validators: Validators,
+}
+
+pub enum FetchResponse {
+    Modified(Box<FetchedCalendar>),
+    /// The calendar did not change since it was fetched with the given validators.
+    NotModified,
+}
+
+impl Id {
+    pub fn from_student_or_person_number(
+        student_number: Option<String>,
+        person_number: Option<String>,
+    ) -> Result<Self, Error> {
+        if let Some(student_number) = student_number {
+            Ok(Self::Student(student_number))
+        } else if let Some(person_number) = person_number {
+            Ok(Self::Person(person_number))
+        } else {
+            Err(QueryError::new("pStud or pPers".to_string()).into())
+        }
+    }
+
+    fn to_query_string(&self) -> String {
+        match self {
+            Self::Student(student_number) => format!("pStud={}", student_number),
+            Self::Person(person_number) => format!("pPers={}", person_number),
+        }
+    }
+
+    /// The bare student or person number.
+    fn number(&self) -> &str {
+        match self {
+            Self::Student(number) | Self::Person(number) => number,
+        }
+    }
+}
+
+impl FetchResponse {
+    fn modified(calendar: IcalCalendar, validators: Validators) -> Self {
+        Self::Modified(Box::new(FetchedCalendar {
+            calendar,
+            validators,
+        }))
+    }
+}
+
+/// Parses the first calendar contained in `bytes`.
+fn parse_calendar(bytes: &[u8]) -> Result<IcalCalendar, FetchError> {
+    let mut calendar = IcalParser::new(bytes);
+    match calendar.next() {
+        Some(Ok(cal)) => Ok(cal),
+        Some(Err(e)) => {
+            error!("Error parsing calendar: {}", e);
+            Err(FetchError::Upstream)
+        }
+        None => {
+            warn!("Source returned an empty calendar");
+            Err(FetchError::Upstream)
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCredentials => write!(f, "The source rejected the provided credentials"),
+            Self::Upstream => write!(f, "The source could not be reached"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+impl From<FetchError> for Error {
+    fn from(e: FetchError) -> Self {
+        match e {
+            FetchError::InvalidCredentials => CalendarError::new().into(),
+            FetchError::Upstream => InternalServerError::new().into(),
+        }
+    }
+}
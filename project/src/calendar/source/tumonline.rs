@@ -0,0 +1,99 @@
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use reqwest::{header, Client, StatusCode};
+use tracing::{error, warn};
+
+use crate::calendar::source::{
+    parse_calendar, CalendarSource, FetchError, FetchResponse, SourceRequest, Validators,
+};
+
+/// Fetches calendars from the iCal export of a CAMPUSonline instance such as TUMOnline.
+pub struct TumOnlineSource {
+    client: Client,
+}
+
+impl TumOnlineSource {
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl CalendarSource for TumOnlineSource {
+    fn fetch(
+        &self,
+        request: SourceRequest,
+    ) -> BoxFuture<'static, Result<FetchResponse, FetchError>> {
+        fetch_calendar(self.client.clone(), request).boxed()
+    }
+}
+
+async fn fetch_calendar(
+    client: Client,
+    request: SourceRequest,
+) -> Result<FetchResponse, FetchError> {
+    let url = format!(
+        "{}?{}&pToken={}",
+        request.base_url,
+        request.id.to_query_string(),
+        request.token
+    );
+
+    let mut remaining_tries = 2;
+    let response = loop {
+        let mut req = client.get(&url).header("User-Agent", "CalProxy/0.1");
+        if let Some(validators) = &request.validators {
+            if let Some(etag) = &validators.etag {
+                req = req.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                req = req.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        match req.send().await {
+            Ok(response) => break response,
+            Err(e) => {
+                remaining_tries -= 1;
+
+                if remaining_tries == 0 {
+                    error!("Error fetching calendar: {}", e);
+                    return Err(FetchError::Upstream);
+                } else {
+                    warn!("Error fetching calendar: {}, retrying", e);
+                }
+            }
+        }
+    };
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(FetchResponse::NotModified);
+    } else if response.status().is_server_error() {
+        error!("TUMOnline responded with status {}", response.status());
+        return Err(FetchError::Upstream);
+    } else if !response.status().is_success() {
+        return Err(FetchError::InvalidCredentials);
+    }
+
+    let header_value = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    };
+    let validators = Validators {
+        etag: header_value(header::ETAG),
+        last_modified: header_value(header::LAST_MODIFIED),
+    };
+
+    let bytes = match response.bytes().await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("Error reading response body: {}", e);
+            return Err(FetchError::Upstream);
+        }
+    };
+
+    let calendar = parse_calendar(bytes.as_ref())?;
+    Ok(FetchResponse::modified(calendar, validators))
+}
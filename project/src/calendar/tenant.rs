@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tum_cal_core::DataSet;
+
+/// A CAMPUSonline instance the proxy can fetch calendars from.
+pub struct Tenant {
+    url: String,
+    data: DataSet,
+}
+
+/// All configured tenants, selected by the `tenant` query parameter.
+#[derive(Clone)]
+pub struct Tenants {
+    default: Arc<Tenant>,
+    tenants: Arc<HashMap<String, Arc<Tenant>>>,
+}
+
+#[derive(Deserialize)]
+struct TenantConfig {
+    url: String,
+    buildings: Option<PathBuf>,
+    courses: Option<PathBuf>,
+}
+
+impl Tenant {
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn data(&self) -> &DataSet {
+        &self.data
+    }
+
+    fn from_config(name: &str, config: TenantConfig, base: &Path) -> Self {
+        let buildings = config
+            .buildings
+            .map(|path| read_json::<HashMap<String, String>>(name, &base.join(path)))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(code, building)| {
+                let code = code.parse::<u16>().unwrap_or_else(|_| {
+                    panic!("Tenant {}: building code {} is not a valid u16", name, code)
+                });
+                (code, building)
+            })
+            .collect();
+
+        let courses = config
+            .courses
+            .map(|path| read_json::<HashMap<String, String>>(name, &base.join(path)))
+            .unwrap_or_default();
+
+        Self {
+            url: config.url,
+            data: DataSet::custom(buildings, courses),
+        }
+    }
+}
+
+impl Tenants {
+    /// Creates the tenant registry from the default upstream URL and an optional JSON file
+    /// mapping tenant names to their URL and building/course data files.
+    ///
+    /// Panics if the tenants file or one of the data files cannot be read, as the proxy
+    /// should not start with a half-loaded configuration.
+    pub fn load(default_url: String, tenants_file: Option<PathBuf>) -> Self {
+        let default = Arc::new(Tenant {
+            url: default_url,
+            data: DataSet::Builtin,
+        });
+
+        let tenants = if let Some(path) = tenants_file {
+            let base = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+            read_json::<HashMap<String, TenantConfig>>("<registry>", &path)
+                .into_iter()
+                .map(|(name, config)| {
+                    let tenant = Tenant::from_config(&name, config, &base);
+                    (name, Arc::new(tenant))
+                })
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        Self {
+            default,
+            tenants: Arc::new(tenants),
+        }
+    }
+
+    pub fn get(&self, name: Option<&str>) -> Option<Arc<Tenant>> {
+        match name {
+            Some(name) => self.tenants.get(name).cloned(),
+            None => Some(self.default.clone()),
+        }
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(tenant: &str, path: &Path) -> T {
+    let file = File::open(path).unwrap_or_else(|e| {
+        panic!(
+            "Tenant {}: could not open {}: {}",
+            tenant,
+            path.display(),
+            e
+        )
+    });
+
+    serde_json::from_reader(file).unwrap_or_else(|e| {
+        panic!(
+            "Tenant {}: invalid JSON in {}: {}",
+            tenant,
+            path.display(),
+            e
+        )
+    })
+}
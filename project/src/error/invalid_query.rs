@@ -0,0 +1,55 @@
+use actix_web::body::BoxBody;
+use actix_web::{HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::ser::SerializeMap;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct InvalidQuery {
+    reason: String,
+}
+
+impl InvalidQuery {
+    pub fn new(reason: String) -> Self {
+        Self { reason }
+    }
+}
+
+impl fmt::Display for InvalidQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid query parameter: {}", self.reason)
+    }
+}
+
+impl std::error::Error for InvalidQuery {}
+
+impl Serialize for InvalidQuery {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_map(Some(3))?;
+        s.serialize_entry("status", &400)?;
+        s.serialize_entry("message", "Invalid Query Parameter")?;
+        s.serialize_entry("reason", &self.reason)?;
+        s.end()
+    }
+}
+
+impl ResponseError for InvalidQuery {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        actix_web::http::StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::BadRequest().json(self)
+    }
+}
+
+impl Responder for InvalidQuery {
+    type Body = BoxBody;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        self.error_response()
+    }
+}
@@ -1,11 +1,15 @@
 mod calendar_error;
 mod internal_server_error;
+mod invalid_query;
 mod method_not_available;
 mod not_found;
 mod query_error;
+mod unknown_tenant;
 
 pub use calendar_error::CalendarError;
 pub use internal_server_error::InternalServerError;
+pub use invalid_query::InvalidQuery;
 pub use method_not_available::MethodNotAvailable;
 pub use not_found::NotFound;
 pub use query_error::QueryError;
+pub use unknown_tenant::UnknownTenant;
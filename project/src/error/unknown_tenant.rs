@@ -0,0 +1,55 @@
+use actix_web::body::BoxBody;
+use actix_web::{HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::ser::SerializeMap;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct UnknownTenant {
+    tenant: String,
+}
+
+impl UnknownTenant {
+    pub fn new(tenant: String) -> Self {
+        Self { tenant }
+    }
+}
+
+impl fmt::Display for UnknownTenant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown tenant: {}", self.tenant)
+    }
+}
+
+impl std::error::Error for UnknownTenant {}
+
+impl Serialize for UnknownTenant {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut s = serializer.serialize_map(Some(3))?;
+        s.serialize_entry("status", &400)?;
+        s.serialize_entry("message", "Unknown Tenant")?;
+        s.serialize_entry("tenant", &self.tenant)?;
+        s.end()
+    }
+}
+
+impl ResponseError for UnknownTenant {
+    fn status_code(&self) -> actix_web::http::StatusCode {
+        actix_web::http::StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::BadRequest().json(self)
+    }
+}
+
+impl Responder for UnknownTenant {
+    type Body = BoxBody;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        self.error_response()
+    }
+}
@@ -1,18 +1,29 @@
 use actix_web::http::Method;
 use actix_web::web::Query;
-use actix_web::{web, Error, HttpResponse, Responder, Scope};
-use reqwest::Client;
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder, Scope};
+use chrono::{NaiveDate, NaiveTime};
 use serde::de::value::StrDeserializer;
 use serde::{Deserialize, Deserializer};
+use std::sync::Arc;
+use tum_cal_core::event_type::ScopedEventType;
+use tum_cal_core::pipeline::{DayOfWeek, GroupSelection};
+use tum_cal_core::{DateExpr, Semester, StageKind};
 
-use crate::calendar::event_type::EventType;
+use crate::calendar::cache::CalendarCache;
+use crate::calendar::coalesce::FetchCoalescer;
+use crate::calendar::format::{Format, FormatOptions};
+use crate::calendar::source::CalendarSource;
+use crate::calendar::tenant::Tenants;
 use crate::calendar::Calendar;
 use crate::error;
-use crate::utils::AppData;
+use crate::utils::{today, AppData};
 
 pub fn service() -> Scope {
     Scope::new("/proxy")
         .route("", web::get().to(handler))
+        .route("/agenda", web::get().to(agenda_handler))
+        .route("/timetable", web::get().to(timetable_handler))
+        .route("/courses", web::get().to(courses_handler))
         .default_service(
             web::route().to(|| async { error::MethodNotAvailable::new(&[&Method::GET]) }),
         )
@@ -26,12 +37,43 @@ pub struct QueryArgs {
     pub person_number: Option<String>,
     #[serde(rename = "pToken")]
     pub token: String,
+    /// Event types to keep, e.g. `VO,IN0001:UE`, optionally only for a course ID or name.
     #[serde(default, deserialize_with = "deserialize_vec_from_csv")]
-    pub include: Option<Vec<EventType>>,
+    pub include: Option<Vec<ScopedEventType>>,
+    /// Event types to remove, scoped like `include`, which takes precedence for each course.
     #[serde(default, deserialize_with = "deserialize_vec_from_csv")]
-    pub exclude: Option<Vec<EventType>>,
+    pub exclude: Option<Vec<ScopedEventType>>,
     #[serde(default, deserialize_with = "deserialize_vec_from_csv")]
     pub ignore: Option<Vec<String>>,
+    pub tenant: Option<String>,
+    /// Transformation stages to run, in order, instead of the default ones.
+    #[serde(default, deserialize_with = "deserialize_vec_from_csv")]
+    pub stages: Option<Vec<StageKind>>,
+    pub format: Option<Format>,
+    /// Any day of the week to show in agenda formats, defaults to the current week.
+    pub week: Option<NaiveDate>,
+    /// Course key as listed by `/courses`, course ID or name, to only get the events of a single
+    /// course.
+    pub course: Option<String>,
+    /// Groups to keep per course, e.g. `IN0001:3,MA0902:Gruppe 05`, the other groups of the
+    /// same courses and event types are removed.
+    #[serde(default, deserialize_with = "deserialize_vec_from_csv")]
+    pub groups: Option<Vec<GroupSelection>>,
+    /// First day of events to keep, also the start of the free/busy range which defaults to
+    /// the start of the current semester, e.g. `2025-10-01`, `today`, `semester` or `-2w`.
+    ///
+    /// Offsets without a sign are positive, e.g. `2w`, which avoids encoding `+` as `%2B`.
+    pub from: Option<DateExpr>,
+    /// Last day of events to keep, given like `from`, also the end of the free/busy range which
+    /// defaults to the end of the current semester.
+    pub to: Option<DateExpr>,
+    /// Weekdays of events to keep, e.g. `mo,di,do`.
+    #[serde(default, deserialize_with = "deserialize_vec_from_csv")]
+    pub weekdays: Option<Vec<DayOfWeek>>,
+    /// Earliest start of events to keep, e.g. `08:00`.
+    pub after: Option<NaiveTime>,
+    /// Latest end of events to keep, e.g. `18:00`.
+    pub before: Option<NaiveTime>,
 }
 
 fn deserialize_vec_from_csv<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
@@ -58,9 +100,69 @@ where
 }
 
 async fn handler(
+    req: HttpRequest,
+    Query(query): Query<QueryArgs>,
+    AppData(source): AppData<Arc<dyn CalendarSource>>,
+    AppData(cache): AppData<CalendarCache>,
+    AppData(coalescer): AppData<FetchCoalescer>,
+    AppData(tenants): AppData<Tenants>,
+) -> impl Responder {
+    let format = Format::negotiate(query.format, &req);
+    let today = today();
+    let semester = Semester::containing(today);
+    let options = FormatOptions {
+        week: query.week.unwrap_or(today),
+        from: match query.from {
+            Some(from) => from
+                .resolve_start(today)
+                .map_err(|e| error::InvalidQuery::new(e.to_string()))?,
+            None => semester.start,
+        },
+        to: match query.to {
+            Some(to) => to
+                .resolve_end(today)
+                .map_err(|e| error::InvalidQuery::new(e.to_string()))?,
+            None => semester.end,
+        },
+    };
+    let calendar = Calendar::from_query(query, source, cache, coalescer, tenants).await?;
+    Ok::<HttpResponse, Error>(calendar.to_response(&req, format, &options))
+}
+
+async fn agenda_handler(
+    req: HttpRequest,
+    Query(query): Query<QueryArgs>,
+    AppData(source): AppData<Arc<dyn CalendarSource>>,
+    AppData(cache): AppData<CalendarCache>,
+    AppData(coalescer): AppData<FetchCoalescer>,
+    AppData(tenants): AppData<Tenants>,
+) -> impl Responder {
+    let week = query.week.unwrap_or_else(today);
+    let calendar = Calendar::from_query(query, source, cache, coalescer, tenants).await?;
+    Ok::<HttpResponse, Error>(calendar.to_agenda_response(&req, week))
+}
+
+async fn timetable_handler(
+    req: HttpRequest,
+    Query(query): Query<QueryArgs>,
+    AppData(source): AppData<Arc<dyn CalendarSource>>,
+    AppData(cache): AppData<CalendarCache>,
+    AppData(coalescer): AppData<FetchCoalescer>,
+    AppData(tenants): AppData<Tenants>,
+) -> impl Responder {
+    let week = query.week.unwrap_or_else(today);
+    let calendar = Calendar::from_query(query, source, cache, coalescer, tenants).await?;
+    Ok::<HttpResponse, Error>(calendar.to_timetable_response(&req, week))
+}
+
+async fn courses_handler(
+    req: HttpRequest,
     Query(query): Query<QueryArgs>,
-    AppData(client): AppData<Client>,
+    AppData(source): AppData<Arc<dyn CalendarSource>>,
+    AppData(cache): AppData<CalendarCache>,
+    AppData(coalescer): AppData<FetchCoalescer>,
+    AppData(tenants): AppData<Tenants>,
 ) -> impl Responder {
-    let calendar = Calendar::from_query(query, client).await?;
-    Ok::<HttpResponse, Error>(calendar.to_response())
+    let calendar = Calendar::from_query(query, source, cache, coalescer, tenants).await?;
+    Ok::<HttpResponse, Error>(calendar.to_courses_response(&req))
 }
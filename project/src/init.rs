@@ -4,16 +4,25 @@ use actix_web::web::QueryConfig;
 use actix_web::App;
 use reqwest::Client;
 use std::env;
-use tracing::error;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{fmt, EnvFilter};
 
-use crate::error::{InternalServerError, QueryError};
+use crate::calendar::cache::CalendarCache;
+use crate::calendar::coalesce::FetchCoalescer;
+use crate::calendar::source::{CalendarSource, FileSource, MemorySource, TumOnlineSource};
+use crate::calendar::tenant::Tenants;
+use crate::error::{InvalidQuery, QueryError};
 
 #[derive(Clone)]
 pub struct AppInit {
-    client: Client,
+    source: Arc<dyn CalendarSource>,
+    cache: CalendarCache,
+    coalescer: FetchCoalescer,
+    tenants: Tenants,
     query_config: QueryConfig,
     pub port: u16,
     pub listen_ip: String,
@@ -32,8 +41,42 @@ impl AppInit {
             .ok()
             .unwrap_or("0.0.0.0".to_string());
 
+        let cache_ttl = env::var("CACHE_TTL")
+            .ok()
+            .and_then(|t| t.parse().ok())
+            .unwrap_or(300);
+
+        let cache_max_entries = env::var("CACHE_MAX_ENTRIES")
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(1000);
+
+        let stale_max_age = env::var("STALE_MAX_AGE")
+            .ok()
+            .and_then(|t| t.parse().ok())
+            .unwrap_or(7 * 24 * 60 * 60);
+
+        let stale_note = env::var("STALE_NOTE")
+            .ok()
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(false);
+
+        let upstream_url = env::var("UPSTREAM_URL")
+            .ok()
+            .unwrap_or("https://campus.tum.de/tumonlinej/ws/termin/ical".to_string());
+
+        let tenants_file = env::var("TENANTS_FILE").ok().map(PathBuf::from);
+
         Self {
-            client: Client::new(),
+            source: calendar_source(),
+            cache: CalendarCache::new(
+                Duration::from_secs(cache_ttl),
+                cache_max_entries,
+                Duration::from_secs(stale_max_age),
+                stale_note,
+            ),
+            coalescer: FetchCoalescer::new(),
+            tenants: Tenants::load(upstream_url, tenants_file),
             query_config: query_config(),
             port,
             listen_ip,
@@ -52,7 +95,10 @@ impl AppInit {
         >,
     > {
         App::new()
-            .app_data(self.client.clone())
+            .app_data(self.source.clone())
+            .app_data(self.cache.clone())
+            .app_data(self.coalescer.clone())
+            .app_data(self.tenants.clone())
             .app_data(self.query_config.clone())
     }
 }
@@ -69,6 +115,26 @@ fn init_logging() {
         .init();
 }
 
+/// Selects the calendar source via `CALENDAR_SOURCE` (`tumonline`, `file` or `memory`),
+/// the latter two reading `<number>.ics` files from `CALENDAR_SOURCE_PATH`.
+///
+/// Only `tumonline` checks tokens, the other sources are meant for development and testing.
+fn calendar_source() -> Arc<dyn CalendarSource> {
+    let source = env::var("CALENDAR_SOURCE").unwrap_or("tumonline".to_string());
+    let path = || {
+        env::var("CALENDAR_SOURCE_PATH")
+            .map(PathBuf::from)
+            .expect("CALENDAR_SOURCE_PATH must be set for file and memory sources")
+    };
+
+    match source.as_str() {
+        "tumonline" => Arc::new(TumOnlineSource::new(Client::new())),
+        "file" => Arc::new(FileSource::new(path())),
+        "memory" => Arc::new(MemorySource::load_directory(&path())),
+        _ => panic!("Unknown calendar source: {}", source),
+    }
+}
+
 fn query_config() -> QueryConfig {
     QueryConfig::default().error_handler(|err, _| {
         let e = err.to_string().replace("Query deserialize error: ", "");
@@ -80,7 +146,6 @@ fn query_config() -> QueryConfig {
             return QueryError::new(missing_parameter.to_string()).into();
         }
 
-        error!("Unknown error happened during query deserialization: {}", e);
-        InternalServerError::new().into()
+        InvalidQuery::new(e).into()
     })
 }
@@ -1,7 +1,9 @@
 use actix_web::dev::Payload;
 use actix_web::{FromRequest, HttpRequest};
+use chrono::{NaiveDate, Utc};
 use std::convert::Infallible;
 use std::future::{ready, Ready};
+use tum_cal_core::TIMEZONE;
 
 #[derive(Debug)]
 pub struct AppData<T>(pub T);
@@ -23,3 +25,8 @@ impl<T: Clone + 'static> FromRequest for AppData<T> {
         }
     }
 }
+
+/// The current day in local time.
+pub fn today() -> NaiveDate {
+    Utc::now().with_timezone(&TIMEZONE).date_naive()
+}